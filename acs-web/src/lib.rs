@@ -87,6 +87,7 @@ struct FrameInfo {
     duration_ms: u32,
     sound_index: Option<usize>,
     image_count: usize,
+    exit_branch: Option<usize>,
     branches: Vec<BranchInfo>,
 }
 
@@ -149,6 +150,32 @@ impl AnimationData {
             .unwrap_or_default()
     }
 
+    /// Get indices of frames that have an exit branch.
+    ///
+    /// When an animation is interrupted, a player should keep playing until it reaches
+    /// one of these frames, then follow that frame's exit branch instead of advancing
+    /// normally. Following exit branches repeatedly walks the character back to its
+    /// rest pose so the next animation can start without a visual jump.
+    #[wasm_bindgen(js_name = "exitFrames")]
+    pub fn exit_frames(&self) -> Vec<u32> {
+        self.frames
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.exit_branch.is_some())
+            .map(|(i, _)| i as u32)
+            .collect()
+    }
+
+    /// Get the sound index for every frame (-1 where a frame has no sound).
+    /// Lets a player schedule sound playback at frame boundaries in a single call.
+    #[wasm_bindgen(js_name = "frameSoundIndices")]
+    pub fn frame_sound_indices(&self) -> Vec<i32> {
+        self.frames
+            .iter()
+            .map(|f| f.sound_index.map(|i| i as i32).unwrap_or(-1))
+            .collect()
+    }
+
     /// Check if any frame in this animation has an associated sound.
     #[wasm_bindgen(getter, js_name = "hasSound")]
     pub fn has_sound(&self) -> bool {
@@ -163,6 +190,8 @@ pub struct AnimationInfo {
     frame_count: usize,
     has_sound: bool,
     return_animation: Option<String>,
+    total_duration_ms: u32,
+    is_playable: bool,
 }
 
 /// A character state grouping animations.
@@ -187,6 +216,140 @@ impl StateInfo {
     }
 }
 
+/// A sound sample format and how many sounds in the file use it.
+#[wasm_bindgen]
+pub struct AudioFormatInfo {
+    #[wasm_bindgen(readonly, js_name = "sampleRate")]
+    pub sample_rate: u32,
+    #[wasm_bindgen(readonly)]
+    pub channels: u16,
+    #[wasm_bindgen(readonly, js_name = "bitsPerSample")]
+    pub bits_per_sample: u16,
+    #[wasm_bindgen(readonly)]
+    pub count: u32,
+}
+
+/// Word balloon appearance, for drawing speech bubbles the way the character expects.
+#[wasm_bindgen]
+pub struct BalloonInfoData {
+    #[wasm_bindgen(readonly, js_name = "numLines")]
+    pub num_lines: u8,
+    #[wasm_bindgen(readonly, js_name = "charsPerLine")]
+    pub chars_per_line: u8,
+    #[wasm_bindgen(readonly, js_name = "fontHeight")]
+    pub font_height: i32,
+    #[wasm_bindgen(readonly, js_name = "fontWeight")]
+    pub font_weight: i32,
+    #[wasm_bindgen(readonly)]
+    pub italic: bool,
+    /// Windows font charset (code page selector), e.g. 0 for ANSI
+    #[wasm_bindgen(readonly)]
+    pub charset: u8,
+    font_name: String,
+    fg_color: Vec<u8>,
+    bg_color: Vec<u8>,
+    border_color: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl BalloonInfoData {
+    /// Font face name.
+    #[wasm_bindgen(getter, js_name = "fontName")]
+    pub fn font_name(&self) -> String {
+        self.font_name.clone()
+    }
+
+    /// Text color as `[r, g, b]`.
+    #[wasm_bindgen(getter, js_name = "fgColor")]
+    pub fn fg_color(&self) -> Vec<u8> {
+        self.fg_color.clone()
+    }
+
+    /// Fill color as `[r, g, b]`.
+    #[wasm_bindgen(getter, js_name = "bgColor")]
+    pub fn bg_color(&self) -> Vec<u8> {
+        self.bg_color.clone()
+    }
+
+    /// Border color as `[r, g, b]`.
+    #[wasm_bindgen(getter, js_name = "borderColor")]
+    pub fn border_color(&self) -> Vec<u8> {
+        self.border_color.clone()
+    }
+}
+
+/// A sound decoded to float PCM, ready to copy into a Web Audio `AudioBuffer`.
+#[wasm_bindgen]
+pub struct SoundPcm {
+    #[wasm_bindgen(readonly, js_name = "sampleRate")]
+    pub sample_rate: u32,
+    #[wasm_bindgen(readonly)]
+    pub channels: u16,
+    samples: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl SoundPcm {
+    /// Interleaved samples in [-1, 1] as Float32Array.
+    #[wasm_bindgen(getter)]
+    pub fn samples(&self) -> js_sys::Float32Array {
+        js_sys::Float32Array::from(&self.samples[..])
+    }
+}
+
+/// Mouth shapes over a sound's playback, as parallel arrays.
+#[wasm_bindgen]
+pub struct LipsyncTimeline {
+    times: Vec<u32>,
+    mouths: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl LipsyncTimeline {
+    /// Start of each window in milliseconds as Uint32Array.
+    #[wasm_bindgen(getter)]
+    pub fn times(&self) -> js_sys::Uint32Array {
+        js_sys::Uint32Array::from(&self.times[..])
+    }
+
+    /// Mouth overlay type for each window (as for `RenderConfig.setMouth`) as Uint8Array.
+    #[wasm_bindgen(getter)]
+    pub fn mouths(&self) -> js_sys::Uint8Array {
+        js_sys::Uint8Array::from(&self.mouths[..])
+    }
+}
+
+/// A problem found by `AcsFile.validate()`.
+#[wasm_bindgen]
+pub struct ValidationIssueData {
+    /// 1 = warning, 2 = error
+    #[wasm_bindgen(readonly)]
+    pub severity: u8,
+    message: String,
+}
+
+#[wasm_bindgen]
+impl ValidationIssueData {
+    /// Human-readable description of the problem.
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl AnimationInfo {
+    fn from_animation(anim: &acs::Animation, role: acs::AnimationRole) -> Self {
+        AnimationInfo {
+            name: anim.name.clone(),
+            frame_count: anim.frames.len(),
+            has_sound: anim.frames.iter().any(|f| f.sound_index.is_some()),
+            return_animation: anim.return_animation.clone(),
+            total_duration_ms: anim.total_duration_ms(),
+            is_playable: role == acs::AnimationRole::Playable,
+        }
+    }
+}
+
 #[wasm_bindgen]
 impl AnimationInfo {
     /// Animation name.
@@ -212,6 +375,57 @@ impl AnimationInfo {
     pub fn return_animation(&self) -> Option<String> {
         self.return_animation.clone()
     }
+
+    /// Sum of all frame durations in milliseconds, ignoring branches.
+    #[wasm_bindgen(getter, js_name = "totalDurationMs")]
+    pub fn total_duration_ms(&self) -> u32 {
+        self.total_duration_ms
+    }
+
+    /// False for Return/Continued helper animations that aren't meant to be played directly.
+    #[wasm_bindgen(getter, js_name = "isPlayable")]
+    pub fn is_playable(&self) -> bool {
+        self.is_playable
+    }
+}
+
+/// Options for `AcsFile.renderFrameWithConfig`. The defaults match `renderFrame`.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct RenderConfig {
+    options: acs::RenderOptions,
+}
+
+#[wasm_bindgen]
+impl RenderConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> RenderConfig {
+        RenderConfig::default()
+    }
+
+    /// Integer nearest-neighbour upscale factor (0 or 1 for none).
+    #[wasm_bindgen(js_name = "setScale")]
+    pub fn set_scale(&mut self, scale: u32) {
+        self.options.scale = scale;
+    }
+
+    /// Composite frames over a solid color instead of leaving them transparent.
+    #[wasm_bindgen(js_name = "setBackground")]
+    pub fn set_background(&mut self, r: u8, g: u8, b: u8, a: u8) {
+        self.options.background = Some([r, g, b, a]);
+    }
+
+    /// Draw the frame's mouth overlay of this type (0-6) on top, for lip-sync.
+    #[wasm_bindgen(js_name = "setMouth")]
+    pub fn set_mouth(&mut self, overlay_type: u8) {
+        self.options.mouth = Some(overlay_type.into());
+    }
+
+    /// Multiply RGB by `factor` (1.0 leaves colors unchanged).
+    #[wasm_bindgen(js_name = "setBrightness")]
+    pub fn set_brightness(&mut self, factor: f32) {
+        self.options.brightness = Some(factor);
+    }
 }
 
 /// An ACS character file.
@@ -220,13 +434,66 @@ pub struct AcsFile {
     inner: Acs,
 }
 
+/// Configures how an `AcsFile` is loaded.
+#[wasm_bindgen]
+pub struct AcsFileBuilder {
+    data: Vec<u8>,
+    lazy: bool,
+}
+
+#[wasm_bindgen]
+impl AcsFileBuilder {
+    /// Start configuring a load of the given ACS file bytes.
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8]) -> AcsFileBuilder {
+        AcsFileBuilder {
+            data: data.to_vec(),
+            lazy: false,
+        }
+    }
+
+    /// Defer animation, image and audio list parsing until first use (see `AcsFile.newLazy`).
+    pub fn lazy(mut self, lazy: bool) -> AcsFileBuilder {
+        self.lazy = lazy;
+        self
+    }
+
+    /// Parse the file with the chosen options.
+    pub fn build(self) -> Result<AcsFile, JsError> {
+        let inner = if self.lazy {
+            Acs::new_lazy(self.data)
+        } else {
+            Acs::new(self.data)
+        }
+        .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(AcsFile { inner })
+    }
+}
+
 #[wasm_bindgen]
 impl AcsFile {
     /// Load an ACS file from a Uint8Array.
     #[wasm_bindgen(constructor)]
     pub fn new(data: &[u8]) -> Result<AcsFile, JsError> {
-        let inner = Acs::new(data.to_vec()).map_err(|e| JsError::new(&e.to_string()))?;
-        Ok(AcsFile { inner })
+        AcsFileBuilder::new(data).build()
+    }
+
+    /// Load an ACS file, parsing only the header and character info up front so the
+    /// main thread isn't blocked for long.
+    ///
+    /// Metadata getters (name, size, palette, states) do no extra work. The first call
+    /// that needs the animation, image or audio lists (e.g. `animationNames`, `getImage`,
+    /// `imageCount`) parses them, so schedule it where a short pause is acceptable.
+    #[wasm_bindgen(js_name = "newLazy")]
+    pub fn new_lazy(data: &[u8]) -> Result<AcsFile, JsError> {
+        AcsFileBuilder::new(data).lazy(true).build()
+    }
+
+    /// Select which localized entry `name`/`description` report (e.g. 1033 or 9 for English).
+    /// Returns false if the file has no entry for that language. Defaults to the first entry.
+    #[wasm_bindgen(js_name = "setLanguage")]
+    pub fn set_language(&mut self, lang_id: u16) -> bool {
+        self.inner.set_preferred_language(lang_id)
     }
 
     /// Character name.
@@ -253,6 +520,60 @@ impl AcsFile {
         self.inner.character_info().height as u32
     }
 
+    /// RGBA of the palette's transparent (chroma-key) color, if the index is valid.
+    #[wasm_bindgen(js_name = "transparentColor")]
+    pub fn transparent_color(&self) -> Option<Vec<u8>> {
+        self.inner
+            .character_info()
+            .transparent_rgba()
+            .map(|rgba| rgba.to_vec())
+    }
+
+    /// Word balloon size, colors and font, or undefined if the character has none.
+    #[wasm_bindgen(js_name = "balloonInfo")]
+    pub fn balloon_info(&self) -> Option<BalloonInfoData> {
+        let balloon = self.inner.balloon_info()?;
+        Some(BalloonInfoData {
+            num_lines: balloon.num_lines,
+            chars_per_line: balloon.chars_per_line,
+            font_height: balloon.font_height,
+            font_weight: balloon.font_weight,
+            italic: balloon.font_italic,
+            charset: balloon.font_charset,
+            font_name: balloon.font_name.clone(),
+            fg_color: balloon.fg_color.to_vec(),
+            bg_color: balloon.bg_color.to_vec(),
+            border_color: balloon.border_color.to_vec(),
+        })
+    }
+
+    /// Character GUID in registry form, e.g. `{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}`.
+    #[wasm_bindgen(getter)]
+    pub fn guid(&self) -> String {
+        self.inner.character_info().guid_string()
+    }
+
+    /// Stable hash of the character as a hex string, for use as a cache key
+    /// (e.g. in IndexedDB). Not a cryptographic hash.
+    #[wasm_bindgen(js_name = "contentHash")]
+    pub fn content_hash(&self) -> String {
+        format!("{:016x}", self.inner.content_hash())
+    }
+
+    /// ACS format version as `[major, minor]`.
+    #[wasm_bindgen(getter, js_name = "formatVersion")]
+    pub fn format_version(&self) -> Vec<u16> {
+        let (major, minor) = self.inner.format_version();
+        vec![major, minor]
+    }
+
+    /// Animation set version as `[major, minor]`.
+    #[wasm_bindgen(getter, js_name = "animSetVersion")]
+    pub fn anim_set_version(&self) -> Vec<u16> {
+        let (major, minor) = self.inner.anim_set_version();
+        vec![major, minor]
+    }
+
     /// List all animation names.
     #[wasm_bindgen(js_name = "animationNames")]
     pub fn animation_names(&self) -> Vec<String> {
@@ -268,28 +589,44 @@ impl AcsFile {
     #[wasm_bindgen(js_name = "playableAnimationNames")]
     pub fn playable_animation_names(&self) -> Vec<String> {
         self.inner
-            .animation_names()
+            .playable_animation_names()
             .into_iter()
-            .filter(|name| {
-                let lower = name.to_lowercase();
-                !lower.ends_with("return") && !lower.ends_with("continued")
-            })
             .map(|s| s.to_string())
             .collect()
     }
 
+    /// Check whether an animation exists (case-insensitive), without throwing.
+    #[wasm_bindgen(js_name = "hasAnimation")]
+    pub fn has_animation(&self, name: &str) -> bool {
+        self.inner.animation_exists(name)
+    }
+
     /// Get number of images in the file.
     #[wasm_bindgen(js_name = "imageCount")]
     pub fn image_count(&self) -> usize {
         self.inner.image_count()
     }
 
+    /// Estimate the bytes needed to decode every image to RGBA, without decoding any.
+    #[wasm_bindgen(js_name = "estimatedDecodedBytes")]
+    pub fn estimated_decoded_bytes(&self) -> usize {
+        self.inner.estimated_decoded_bytes()
+    }
+
     /// Get number of sounds in the file.
     #[wasm_bindgen(js_name = "soundCount")]
     pub fn sound_count(&self) -> usize {
         self.inner.sound_count()
     }
 
+    /// Get a sound's playback length in milliseconds, for scheduling frame sounds.
+    #[wasm_bindgen(js_name = "soundDurationMs")]
+    pub fn sound_duration_ms(&self, index: usize) -> Result<u32, JsError> {
+        self.inner
+            .sound_duration_ms(index)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
     /// Get a single image by index as RGBA data.
     #[wasm_bindgen(js_name = "getImage")]
     pub fn get_image(&self, index: usize) -> Result<ImageData, JsError> {
@@ -332,6 +669,7 @@ impl AcsFile {
                     duration_ms: f.duration_ms,
                     sound_index: f.sound_index,
                     image_count: f.images.len(),
+                    exit_branch: f.exit_branch,
                     branches: f
                         .branches
                         .iter()
@@ -347,6 +685,15 @@ impl AcsFile {
         Ok(result)
     }
 
+    /// Decode and cache every image an animation uses so playback doesn't stutter
+    /// on the first frame. Trades memory for latency.
+    #[wasm_bindgen(js_name = "preloadAnimation")]
+    pub fn preload_animation(&mut self, name: &str) -> Result<(), JsError> {
+        self.inner
+            .preload_animation_images(name)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
     /// Render a complete animation frame by compositing all frame images.
     /// Returns RGBA image data at the character's full dimensions.
     #[wasm_bindgen(js_name = "renderFrame")]
@@ -363,6 +710,43 @@ impl AcsFile {
         })
     }
 
+    /// Render a complete animation frame with the given options.
+    #[wasm_bindgen(js_name = "renderFrameWithConfig")]
+    pub fn render_frame_with_config(
+        &self,
+        animation: &str,
+        frame_index: usize,
+        config: &RenderConfig,
+    ) -> Result<ImageData, JsError> {
+        let img = self
+            .inner
+            .render_frame_with_options(animation, frame_index, &config.options)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+
+        Ok(ImageData {
+            width: img.width,
+            height: img.height,
+            data: img.data,
+        })
+    }
+
+    /// Render a complete animation frame and encode it as PNG.
+    /// Useful for `<img>` sources and downloads without a canvas round-trip.
+    #[cfg(feature = "png")]
+    #[wasm_bindgen(js_name = "renderFramePng")]
+    pub fn render_frame_png(
+        &mut self,
+        animation: &str,
+        frame_index: usize,
+    ) -> Result<js_sys::Uint8Array, JsError> {
+        let png = self
+            .inner
+            .render_frame_png(animation, frame_index)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+
+        Ok(js_sys::Uint8Array::from(&png[..]))
+    }
+
     /// Get sound data by index as WAV bytes.
     #[wasm_bindgen(js_name = "getSound")]
     pub fn get_sound(&self, index: usize) -> Result<js_sys::Uint8Array, JsError> {
@@ -388,6 +772,51 @@ impl AcsFile {
         Ok(buffer)
     }
 
+    /// Get a sound as mono 16-bit WAV bytes, averaging stereo channels to save memory.
+    /// Mono sounds are returned unchanged.
+    #[wasm_bindgen(js_name = "getSoundMono")]
+    pub fn get_sound_mono(&self, index: usize) -> Result<js_sys::Uint8Array, JsError> {
+        let wav = self
+            .inner
+            .sound_mono(index)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(js_sys::Uint8Array::from(&wav[..]))
+    }
+
+    /// Decode a 16-bit PCM sound to interleaved float samples, skipping `decodeAudioData`.
+    #[wasm_bindgen(js_name = "getSoundPcm")]
+    pub fn get_sound_pcm(&self, index: usize) -> Result<SoundPcm, JsError> {
+        let (format, samples) = self
+            .inner
+            .sound(index)
+            .and_then(|sound| sound.pcm16())
+            .map_err(|e| JsError::new(&e.to_string()))?;
+
+        Ok(SoundPcm {
+            sample_rate: format.sample_rate,
+            channels: format.channels,
+            samples: samples.iter().map(|&s| s as f32 / 32768.0).collect(),
+        })
+    }
+
+    /// Mouth shape for each `windowMs` of a 16-bit sound, for lip-syncing its playback.
+    #[wasm_bindgen(js_name = "lipsyncTimeline")]
+    pub fn lipsync_timeline(
+        &self,
+        index: usize,
+        window_ms: u32,
+    ) -> Result<LipsyncTimeline, JsError> {
+        let timeline = self
+            .inner
+            .lipsync_timeline(index, window_ms)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+
+        Ok(LipsyncTimeline {
+            times: timeline.iter().map(|&(t, _)| t).collect(),
+            mouths: timeline.into_iter().map(|(_, m)| m.into()).collect(),
+        })
+    }
+
     /// Get summary info for all animations (useful for building UI lists).
     #[wasm_bindgen(js_name = "getAllAnimationInfo")]
     pub fn get_all_animation_info(&mut self) -> Vec<AnimationInfo> {
@@ -401,18 +830,57 @@ impl AcsFile {
         names
             .into_iter()
             .filter_map(|name| {
+                let role = self.inner.animation_role(&name);
                 let anim = self.inner.animation(&name).ok()?;
-                let has_sound = anim.frames.iter().any(|f| f.sound_index.is_some());
-                Some(AnimationInfo {
-                    name: anim.name.clone(),
-                    frame_count: anim.frames.len(),
-                    has_sound,
-                    return_animation: anim.return_animation.clone(),
-                })
+                Some(AnimationInfo::from_animation(anim, role))
             })
             .collect()
     }
 
+    /// Invoke `callback` with an `AnimationInfo` for each animation, without building a
+    /// list first. Lets JS stream large animation lists into the DOM.
+    /// If the callback throws, iteration stops and the error is rethrown.
+    ///
+    /// Animations that fail to load are skipped; returns a `"name: error"` message for
+    /// each of them.
+    #[wasm_bindgen(js_name = "forEachAnimationInfo")]
+    pub fn for_each_animation_info(
+        &mut self,
+        callback: &js_sys::Function,
+    ) -> Result<Vec<String>, JsError> {
+        let names: Vec<String> = self
+            .inner
+            .animation_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let mut failures = Vec::new();
+        for name in names {
+            let role = self.inner.animation_role(&name);
+            let anim = match self.inner.animation(&name) {
+                Ok(anim) => anim,
+                Err(e) => {
+                    failures.push(format!("{}: {}", name, e));
+                    continue;
+                }
+            };
+            let info = AnimationInfo::from_animation(anim, role);
+            callback
+                .call1(&JsValue::NULL, &JsValue::from(info))
+                .map_err(|e| {
+                    let message = e
+                        .dyn_ref::<js_sys::Error>()
+                        .map(|err| String::from(err.message()))
+                        .or_else(|| e.as_string())
+                        .unwrap_or_else(|| "animation callback threw".to_string());
+                    JsError::new(&message)
+                })?;
+        }
+
+        Ok(failures)
+    }
+
     /// Get all character states (animation groupings).
     #[wasm_bindgen(js_name = "getStates")]
     pub fn get_states(&self) -> Vec<StateInfo> {
@@ -425,4 +893,39 @@ impl AcsFile {
             })
             .collect()
     }
+
+    /// Run structural checks over the whole file (image, sound, branch and return
+    /// references, palette, WAV headers). An empty list means the file is consistent.
+    pub fn validate(&mut self) -> Vec<ValidationIssueData> {
+        self.inner
+            .validate()
+            .into_iter()
+            .map(|issue| ValidationIssueData {
+                severity: issue.severity as u8,
+                message: issue.message,
+            })
+            .collect()
+    }
+
+    /// Get the distinct sound formats in the file, one entry per
+    /// (sampleRate, channels, bitsPerSample) combination.
+    /// More than one entry means the sounds need resampling to share an AudioContext rate.
+    #[wasm_bindgen(js_name = "audioFormatSummary")]
+    pub fn audio_format_summary(&self) -> Result<Vec<AudioFormatInfo>, JsError> {
+        let summary = self
+            .inner
+            .audio_format_summary()
+            .map_err(|e| JsError::new(&e.to_string()))?;
+
+        Ok(summary
+            .formats
+            .into_iter()
+            .map(|(format, count)| AudioFormatInfo {
+                sample_rate: format.sample_rate,
+                channels: format.channels,
+                bits_per_sample: format.bits_per_sample,
+                count: count as u32,
+            })
+            .collect())
+    }
 }
@@ -0,0 +1,209 @@
+//! Tests run in a browser or Node via `wasm-pack test`.
+
+#![cfg(target_arch = "wasm32")]
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use acs_web::{AcsFile, RenderConfig};
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_test::*;
+
+const BONZI: &[u8] = include_bytes!("../../notes/files/Bonzi.acs");
+const CLIPPIT: &[u8] = include_bytes!("../../notes/files/clippit.acs");
+
+#[cfg(feature = "png")]
+#[wasm_bindgen_test]
+fn render_frame_png_has_magic() {
+    let mut acs = AcsFile::new(BONZI).unwrap();
+    let png = acs.render_frame_png("Explain", 0).unwrap().to_vec();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+}
+
+#[wasm_bindgen_test]
+fn render_config_scales_frame() {
+    let acs = AcsFile::new(BONZI).unwrap();
+    let plain = acs.render_frame("Explain", 0).unwrap();
+
+    let default = acs
+        .render_frame_with_config("Explain", 0, &RenderConfig::new())
+        .unwrap();
+    assert_eq!(default.data().to_vec(), plain.data().to_vec());
+
+    let mut config = RenderConfig::new();
+    config.set_scale(2);
+    let scaled = acs.render_frame_with_config("Explain", 0, &config).unwrap();
+    assert_eq!(
+        (scaled.width, scaled.height),
+        (plain.width * 2, plain.height * 2)
+    );
+}
+
+#[wasm_bindgen_test]
+fn animation_info_reports_duration_and_playability() {
+    let mut acs = AcsFile::new(BONZI).unwrap();
+    let infos = acs.get_all_animation_info();
+
+    let find = |name: &str| {
+        infos
+            .iter()
+            .find(|i| i.name().eq_ignore_ascii_case(name))
+            .unwrap()
+    };
+
+    assert!(!find("ReadReturn").is_playable());
+
+    let greet = find("Greet");
+    assert!(greet.is_playable());
+    let anim = acs.get_animation("Greet").unwrap();
+    let expected: u32 = (0..greet.frame_count())
+        .map(|i| anim.get_frame(i).unwrap().duration_ms)
+        .sum();
+    assert_eq!(greet.total_duration_ms(), expected);
+}
+
+#[wasm_bindgen_test]
+fn exit_frames_match_exit_branches() {
+    let mut acs = AcsFile::new(CLIPPIT).unwrap();
+    let anim = acs.get_animation("IdleFingerTap").unwrap();
+    let exit_frames = anim.exit_frames();
+    assert!(!exit_frames.is_empty());
+    assert!(
+        exit_frames
+            .iter()
+            .all(|&i| (i as usize) < anim.frame_count())
+    );
+}
+
+#[wasm_bindgen_test]
+fn frame_sound_indices_match_frames() {
+    let mut acs = AcsFile::new(BONZI).unwrap();
+    let anim = acs.get_animation("Reading").unwrap();
+    let indices = anim.frame_sound_indices();
+    assert_eq!(indices.len(), anim.frame_count());
+    for (i, sound_index) in indices.iter().enumerate() {
+        assert_eq!(*sound_index, anim.get_frame(i).unwrap().sound_index);
+    }
+    assert!(indices.iter().any(|&i| i >= 0));
+}
+
+#[wasm_bindgen_test]
+fn set_language_switches_name() {
+    let mut acs = AcsFile::new(CLIPPIT).unwrap();
+    assert!(acs.set_language(1031));
+    assert_eq!(acs.name(), "Karl Klammer");
+    assert!(acs.set_language(9));
+    assert_eq!(acs.name(), "Clippit");
+    assert!(!acs.set_language(0xFFFF));
+    assert_eq!(acs.name(), "Clippit");
+}
+
+#[wasm_bindgen_test]
+fn for_each_animation_info_visits_every_animation() {
+    let mut acs = AcsFile::new(BONZI).unwrap();
+    let count = Rc::new(Cell::new(0));
+    let counter = count.clone();
+    let callback = Closure::<dyn FnMut(JsValue)>::new(move |_: JsValue| {
+        counter.set(counter.get() + 1);
+    });
+
+    let failures = acs
+        .for_each_animation_info(callback.as_ref().unchecked_ref())
+        .unwrap();
+    assert!(failures.is_empty());
+    assert_eq!(count.get(), acs.animation_names().len());
+}
+
+#[wasm_bindgen_test]
+fn audio_format_summary_counts_every_sound() {
+    let acs = AcsFile::new(BONZI).unwrap();
+    let formats = acs.audio_format_summary().unwrap();
+    assert!(!formats.is_empty());
+    let total: u32 = formats.iter().map(|f| f.count).sum();
+    assert_eq!(total as usize, acs.sound_count());
+}
+
+#[wasm_bindgen_test]
+fn sound_duration_is_nonzero() {
+    let acs = AcsFile::new(BONZI).unwrap();
+    assert!(acs.sound_duration_ms(0).unwrap() > 0);
+    assert!(acs.sound_duration_ms(acs.sound_count()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn sound_pcm_matches_wav_header() {
+    let acs = AcsFile::new(BONZI).unwrap();
+    let core = acs::Acs::new(BONZI.to_vec()).unwrap();
+    for index in 0..acs.sound_count() {
+        let format = core.sound(index).unwrap().format().unwrap();
+        let Ok(pcm) = acs.get_sound_pcm(index) else {
+            // Only 16-bit sounds can be decoded
+            assert_ne!(format.bits_per_sample, 16);
+            continue;
+        };
+        assert_eq!(pcm.sample_rate, format.sample_rate);
+        assert_eq!(pcm.channels, format.channels);
+        let samples = pcm.samples().to_vec();
+        assert_eq!(samples.len() % pcm.channels as usize, 0);
+        assert!(samples.iter().all(|s| (-1.0..1.0).contains(s)));
+    }
+}
+
+#[wasm_bindgen_test]
+fn lipsync_timeline_matches_core() {
+    let acs = AcsFile::new(BONZI).unwrap();
+    let core = acs::Acs::new(BONZI.to_vec()).unwrap();
+    let expected = core.lipsync_timeline(0, 50).unwrap();
+
+    let timeline = acs.lipsync_timeline(0, 50).unwrap();
+    let times = timeline.times().to_vec();
+    let mouths = timeline.mouths().to_vec();
+    assert!(!times.is_empty());
+    assert_eq!(times.len(), mouths.len());
+    for (i, &(t, mouth)) in expected.iter().enumerate() {
+        assert_eq!(times[i], t);
+        assert_eq!(mouths[i], u8::from(mouth));
+    }
+}
+
+#[wasm_bindgen_test]
+fn validate_reports_broken_sound() {
+    assert!(AcsFile::new(BONZI).unwrap().validate().is_empty());
+
+    let mut data = BONZI.to_vec();
+    let riff = data.windows(4).position(|w| w == b"RIFF").unwrap();
+    data[riff..riff + 4].copy_from_slice(b"JUNK");
+
+    let issues = AcsFile::new(&data).unwrap().validate();
+    assert!(!issues.is_empty());
+    assert_eq!(issues[0].severity, 2);
+}
+
+#[wasm_bindgen_test]
+fn balloon_info_matches_core() {
+    let balloon = AcsFile::new(BONZI).unwrap().balloon_info().unwrap();
+    let core = acs::Acs::new(BONZI.to_vec()).unwrap();
+    let expected = core.balloon_info().unwrap();
+    assert_eq!(balloon.italic, expected.font_italic);
+    assert_eq!(balloon.charset, expected.font_charset);
+    assert_eq!(balloon.font_name(), expected.font_name);
+    assert_eq!(balloon.fg_color(), expected.fg_color);
+}
+
+#[wasm_bindgen_test]
+fn guid_is_braced() {
+    let guid = AcsFile::new(BONZI).unwrap().guid();
+    assert_eq!(guid.len(), 38);
+    assert!(guid.starts_with('{') && guid.ends_with('}'));
+}
+
+#[wasm_bindgen_test]
+fn new_lazy_parses_lists_on_demand() {
+    let lazy = AcsFile::new_lazy(BONZI).unwrap();
+    assert_eq!(lazy.name(), "Bonzi");
+    assert_eq!(
+        lazy.animation_names(),
+        AcsFile::new(BONZI).unwrap().animation_names()
+    );
+}
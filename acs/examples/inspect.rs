@@ -1,15 +1,26 @@
-use std::fs;
 use acs::Acs;
+use std::fs;
 
 fn format_guid(bytes: &[u8; 16]) -> String {
     // GUID format: {XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}
     format!(
         "{{{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
-        bytes[3], bytes[2], bytes[1], bytes[0],
-        bytes[5], bytes[4],
-        bytes[7], bytes[6],
-        bytes[8], bytes[9],
-        bytes[10], bytes[11], bytes[12], bytes[13], bytes[14], bytes[15]
+        bytes[3],
+        bytes[2],
+        bytes[1],
+        bytes[0],
+        bytes[5],
+        bytes[4],
+        bytes[7],
+        bytes[6],
+        bytes[8],
+        bytes[9],
+        bytes[10],
+        bytes[11],
+        bytes[12],
+        bytes[13],
+        bytes[14],
+        bytes[15]
     )
 }
 
@@ -19,6 +30,7 @@ fn main() {
     let mut acs = Acs::new(data).expect("parse");
 
     println!("Character: {}", acs.character_info().name);
+    println!("GUID: {}", acs.character_info().guid_string());
 
     // Print voice info
     if let Some(ref voice) = acs.character_info().voice_info {
@@ -41,14 +53,18 @@ fn main() {
     // Show specific animation details
     let filter = std::env::args().nth(2);
 
-    let names: Vec<String> = acs.animation_names().iter().map(|s| s.to_string()).collect();
+    let names: Vec<String> = acs
+        .animation_names()
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
 
     println!("\nAnimations with transitions:");
     for name in names {
-        if let Some(ref f) = filter {
-            if !name.to_lowercase().contains(&f.to_lowercase()) {
-                continue;
-            }
+        if let Some(ref f) = filter
+            && !name.to_lowercase().contains(&f.to_lowercase())
+        {
+            continue;
         }
         if let Ok(anim) = acs.animation(&name) {
             let return_anim = anim.return_animation.as_deref().unwrap_or("(none)");
@@ -57,15 +73,28 @@ fn main() {
                 acs::TransitionType::UseExitBranch => "UseExitBranch",
                 acs::TransitionType::None => "None",
             };
-            println!("  {} ({} frames) -> {} (type: {})", name, anim.frames.len(), return_anim, trans_type);
+            println!(
+                "  {} ({} frames) -> {} (type: {})",
+                name,
+                anim.frames.len(),
+                return_anim,
+                trans_type
+            );
 
             // Show exit branches for last few frames if using exit branches
             if anim.transition_type == acs::TransitionType::UseExitBranch {
                 for (i, frame) in anim.frames.iter().enumerate() {
                     if frame.exit_branch.is_some() || !frame.branches.is_empty() {
-                        println!("    frame {}: exit_branch={:?}, branches={:?}",
-                            i, frame.exit_branch,
-                            frame.branches.iter().map(|b| (b.frame_index, b.probability)).collect::<Vec<_>>());
+                        println!(
+                            "    frame {}: exit_branch={:?}, branches={:?}",
+                            i,
+                            frame.exit_branch,
+                            frame
+                                .branches
+                                .iter()
+                                .map(|b| (b.frame_index, b.probability))
+                                .collect::<Vec<_>>()
+                        );
                     }
                 }
             }
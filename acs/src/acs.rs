@@ -2,21 +2,68 @@
 //!
 //! Provides lazy extraction of images, animations, and audio from ACS files.
 
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
 
 use crate::compression::{DecompressionError, decompress};
+use crate::export::encode_bmp_indexed;
 use crate::reader::{
-    AcsHeader, AcsReader, AudioEntry, ImageEntry, RawAnimationInfo, RawCharacterInfo, RawImageInfo,
-    ReaderError, VoiceInfo,
+    AcsHeader, AcsReader, AnimationEntry, AudioEntry, BalloonInfo, ImageEntry, LocalizedInfo,
+    ParseMode, RawAnimationInfo, RawCharacterInfo, RawFrameInfo, RawImageInfo, ReaderError,
+    TrayIcon, VoiceInfo,
 };
+use crate::render::{ImageOrder, RenderOptions};
+use crate::timeline::Timeline;
+use crate::wav::WavError;
 
 #[derive(Debug)]
 pub enum AcsError {
     Reader(ReaderError),
     Decompression(DecompressionError),
     InvalidImageIndex(usize),
+    /// A frame index past the end of an animation
+    InvalidFrameIndex {
+        animation: String,
+        index: usize,
+    },
     InvalidSoundIndex(usize),
     AnimationNotFound(String),
+    InvalidPaletteLength(usize),
+    /// Pixel data whose length doesn't match the image's dimensions
+    InvalidImageData {
+        width: u32,
+        height: u32,
+        len: usize,
+    },
+    InvalidWav(WavError),
+    /// Scaling an image by `factor` would overflow its dimensions
+    ScaleOverflow {
+        width: u32,
+        height: u32,
+        factor: u32,
+    },
+    /// A sprite sheet of `cols` x `rows` cells would overflow its dimensions
+    SheetOverflow {
+        cols: u32,
+        rows: u32,
+        cell_width: u32,
+        cell_height: u32,
+    },
+    DuplicateAnimation(String),
+    /// A frame's exit branch points past the end of its animation
+    InvalidExitBranch {
+        animation: String,
+        frame: usize,
+        target: usize,
+    },
+    Io(std::io::Error),
+    #[cfg(feature = "png")]
+    PngEncoding(png::EncodingError),
+    #[cfg(feature = "audio-transcode")]
+    OpusEncoding(audiopus::Error),
 }
 
 impl fmt::Display for AcsError {
@@ -25,8 +72,52 @@ impl fmt::Display for AcsError {
             Self::Reader(e) => write!(f, "reader error: {}", e),
             Self::Decompression(e) => write!(f, "decompression error: {}", e),
             Self::InvalidImageIndex(i) => write!(f, "invalid image index: {}", i),
+            Self::InvalidFrameIndex { animation, index } => {
+                write!(f, "{}: invalid frame index {}", animation, index)
+            }
             Self::InvalidSoundIndex(i) => write!(f, "invalid sound index: {}", i),
             Self::AnimationNotFound(name) => write!(f, "animation not found: {}", name),
+            Self::InvalidPaletteLength(len) => write!(f, "invalid palette length: {}", len),
+            Self::InvalidImageData { width, height, len } => write!(
+                f,
+                "{} bytes of pixel data for a {}x{} image",
+                len, width, height
+            ),
+            Self::InvalidWav(e) => write!(f, "invalid WAV data: {}", e),
+            Self::ScaleOverflow {
+                width,
+                height,
+                factor,
+            } => write!(
+                f,
+                "scaling a {}x{} image by {} overflows",
+                width, height, factor
+            ),
+            Self::SheetOverflow {
+                cols,
+                rows,
+                cell_width,
+                cell_height,
+            } => write!(
+                f,
+                "a sheet of {}x{} cells of {}x{} overflows",
+                cols, rows, cell_width, cell_height
+            ),
+            Self::DuplicateAnimation(name) => write!(f, "animation already exists: {}", name),
+            Self::InvalidExitBranch {
+                animation,
+                frame,
+                target,
+            } => write!(
+                f,
+                "{}: frame {} exits to missing frame {}",
+                animation, frame, target
+            ),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            #[cfg(feature = "png")]
+            Self::PngEncoding(e) => write!(f, "PNG encoding error: {}", e),
+            #[cfg(feature = "audio-transcode")]
+            Self::OpusEncoding(e) => write!(f, "Opus encoding error: {}", e),
         }
     }
 }
@@ -36,6 +127,12 @@ impl std::error::Error for AcsError {
         match self {
             Self::Reader(e) => Some(e),
             Self::Decompression(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::InvalidWav(e) => Some(e),
+            #[cfg(feature = "png")]
+            Self::PngEncoding(e) => Some(e),
+            #[cfg(feature = "audio-transcode")]
+            Self::OpusEncoding(e) => Some(e),
             _ => None,
         }
     }
@@ -53,6 +150,32 @@ impl From<DecompressionError> for AcsError {
     }
 }
 
+impl From<WavError> for AcsError {
+    fn from(e: WavError) -> Self {
+        Self::InvalidWav(e)
+    }
+}
+
+impl From<std::io::Error> for AcsError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(feature = "png")]
+impl From<png::EncodingError> for AcsError {
+    fn from(e: png::EncodingError) -> Self {
+        Self::PngEncoding(e)
+    }
+}
+
+#[cfg(feature = "audio-transcode")]
+impl From<audiopus::Error> for AcsError {
+    fn from(e: audiopus::Error) -> Self {
+        Self::OpusEncoding(e)
+    }
+}
+
 /// Raw RGBA image data (WASM-friendly, no dependencies)
 #[derive(Debug, Clone)]
 pub struct Image {
@@ -63,6 +186,7 @@ pub struct Image {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Animation {
     pub name: String,
     pub frames: Vec<Frame>,
@@ -70,8 +194,26 @@ pub struct Animation {
     pub transition_type: TransitionType,
 }
 
+impl Animation {
+    /// Sum of all frame durations in milliseconds, saturating at `u32::MAX`.
+    pub fn total_duration_ms(&self) -> u32 {
+        self.frames
+            .iter()
+            .fold(0u32, |acc, f| acc.saturating_add(f.duration_ms))
+    }
+
+    /// Sum of all frame durations in seconds, for engines that work in `f32` time.
+    pub fn total_duration_secs(&self) -> f32 {
+        self.total_duration_ms() as f32 / 1000.0
+    }
+}
+
 /// How an animation transitions when it completes.
+///
+/// Converts to and from its variant name (e.g. `"UseExitBranch"`) via `Display` and
+/// `FromStr`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransitionType {
     /// Type 0: Play the `return_animation` when complete
     UseReturnAnimation,
@@ -81,6 +223,17 @@ pub enum TransitionType {
     None,
 }
 
+/// What an animation is for, as implied by its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationRole {
+    /// Meant to be played directly
+    Playable,
+    /// A `...Return` animation chained after its parent to get back to rest
+    ReturnHelper,
+    /// A `...Continued` animation chained while its parent's action is ongoing
+    ContinuedHelper,
+}
+
 impl From<u8> for TransitionType {
     fn from(val: u8) -> Self {
         match val {
@@ -92,18 +245,88 @@ impl From<u8> for TransitionType {
     }
 }
 
+impl TransitionType {
+    fn name(self) -> &'static str {
+        match self {
+            Self::UseReturnAnimation => "UseReturnAnimation",
+            Self::UseExitBranch => "UseExitBranch",
+            Self::None => "None",
+        }
+    }
+}
+
+impl fmt::Display for TransitionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Error returned when parsing an unrecognized `TransitionType` name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTransitionTypeError(pub String);
+
+impl fmt::Display for ParseTransitionTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown transition type: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseTransitionTypeError {}
+
+impl std::str::FromStr for TransitionType {
+    type Err = ParseTransitionTypeError;
+
+    /// Parse a variant name, ignoring ASCII case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::UseReturnAnimation, Self::UseExitBranch, Self::None]
+            .into_iter()
+            .find(|t| t.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ParseTransitionTypeError(s.to_string()))
+    }
+}
+
+impl From<TransitionType> for u8 {
+    fn from(val: TransitionType) -> Self {
+        match val {
+            TransitionType::UseReturnAnimation => 0,
+            TransitionType::UseExitBranch => 1,
+            TransitionType::None => 2,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Frame {
     pub images: Vec<FrameImage>,
     /// Duration in milliseconds (original is in 1/100 sec, we convert)
     pub duration_ms: u32,
     pub sound_index: Option<usize>,
+    /// Frame to jump to when the animation is interrupted (e.g. by `Stop` or a new
+    /// request), so the character can wind down gracefully instead of cutting off.
+    /// One past the last frame means "end now"; anything further out is dropped on load.
     pub exit_branch: Option<usize>,
     pub branches: Vec<Branch>,
     pub overlays: Vec<Overlay>,
 }
 
+impl Frame {
+    /// Frame duration in seconds, for engines that work in `f32` time.
+    pub fn duration_secs(&self) -> f32 {
+        self.duration_ms as f32 / 1000.0
+    }
+
+    /// Every image index this frame draws, from frame images and then overlays.
+    pub fn image_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.images
+            .iter()
+            .map(|img| img.image_index)
+            .chain(self.overlays.iter().map(|o| o.image_index))
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrameImage {
     pub image_index: usize,
     pub x: i16,
@@ -111,12 +334,14 @@ pub struct FrameImage {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Branch {
     pub frame_index: usize,
     pub probability: u16,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Overlay {
     pub overlay_type: OverlayType,
     pub replace_enabled: bool,
@@ -127,7 +352,8 @@ pub struct Overlay {
     pub height: u16,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OverlayType {
     MouthClosed,
     MouthWide1,
@@ -154,7 +380,53 @@ impl From<u8> for OverlayType {
     }
 }
 
+impl From<OverlayType> for u8 {
+    fn from(val: OverlayType) -> Self {
+        match val {
+            OverlayType::MouthClosed => 0,
+            OverlayType::MouthWide1 => 1,
+            OverlayType::MouthWide2 => 2,
+            OverlayType::MouthWide3 => 3,
+            OverlayType::MouthWide4 => 4,
+            OverlayType::MouthMedium => 5,
+            OverlayType::MouthNarrow => 6,
+            OverlayType::Unknown(n) => n,
+        }
+    }
+}
+
+impl OverlayType {
+    /// How far open the mouth is, from 0 (closed) upward, for ordering or interpolating
+    /// between shapes. Unknown types count as closed.
+    pub fn openness(&self) -> u8 {
+        match self {
+            Self::MouthClosed | Self::Unknown(_) => 0,
+            Self::MouthNarrow => 1,
+            Self::MouthMedium => 2,
+            Self::MouthWide1 => 3,
+            Self::MouthWide2 => 4,
+            Self::MouthWide3 => 5,
+            Self::MouthWide4 => 6,
+        }
+    }
+
+    /// Short human-readable name for debug UIs.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::MouthClosed => "closed",
+            Self::MouthWide1 => "wide 1",
+            Self::MouthWide2 => "wide 2",
+            Self::MouthWide3 => "wide 3",
+            Self::MouthWide4 => "wide 4",
+            Self::MouthMedium => "medium",
+            Self::MouthNarrow => "narrow",
+            Self::Unknown(_) => "unknown",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CharacterInfo {
     pub name: String,
     pub description: String,
@@ -163,11 +435,174 @@ pub struct CharacterInfo {
     pub transparent_color: u8,
     /// RGBA palette (256 entries max)
     pub palette: Vec<[u8; 4]>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_guid"))]
     pub guid: [u8; 16],
     /// Voice TTS settings from the ACS file
     pub voice_info: Option<VoiceInfo>,
 }
 
+impl CharacterInfo {
+    /// Resolve the transparent palette index to its RGBA color.
+    ///
+    /// Useful for flattening onto a matching chroma-key background. Returns `None` if
+    /// the index is outside the palette.
+    pub fn transparent_rgba(&self) -> Option<[u8; 4]> {
+        self.palette.get(self.transparent_color as usize).copied()
+    }
+
+    /// Format the character GUID in registry form, e.g.
+    /// `{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}`.
+    ///
+    /// The first three groups are stored little-endian and the last two as raw bytes,
+    /// matching the Windows `GUID` layout.
+    pub fn guid_string(&self) -> String {
+        let g = &self.guid;
+        format!(
+            "{{{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+            g[3],
+            g[2],
+            g[1],
+            g[0],
+            g[5],
+            g[4],
+            g[7],
+            g[6],
+            g[8],
+            g[9],
+            g[10],
+            g[11],
+            g[12],
+            g[13],
+            g[14],
+            g[15]
+        )
+    }
+}
+
+/// 64-bit FNV-1a, used for hashes that must be stable across builds and platforms.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Which animations use an image, from `Acs::animations_using_image`.
+#[derive(Debug)]
+pub struct ImageUsage {
+    /// `(animation name, frame indices)` for each animation that uses the image
+    pub animations: Vec<(String, Vec<usize>)>,
+    /// Name and error of each animation that failed to parse and couldn't be checked
+    pub failures: Vec<(String, AcsError)>,
+}
+
+/// Everything parsed from the character info block, gathered into one struct.
+#[derive(Debug, Clone)]
+pub struct FullCharacterInfo {
+    /// Name in the selected language (see `Acs::set_preferred_language`)
+    pub name: String,
+    /// Description in the selected language
+    pub description: String,
+    pub guid: [u8; 16],
+    pub width: u16,
+    pub height: u16,
+    pub transparent_color: u8,
+    /// RGBA palette (256 entries max)
+    pub palette: Vec<[u8; 4]>,
+    /// ACS format version as `(major, minor)`
+    pub format_version: (u16, u16),
+    /// Animation set version as `(major, minor)`
+    pub anim_set_version: (u16, u16),
+    /// Raw character flags (voice output, balloon behavior, standard animation set)
+    pub flags: u32,
+    pub voice_info: Option<VoiceInfo>,
+    /// `None` for characters whose flags leave out the word balloon
+    pub balloon_info: Option<BalloonInfo>,
+    /// Every localized name/description entry in the file
+    pub localized_info: Vec<LocalizedInfo>,
+    pub tray_icon: Option<TrayIcon>,
+    pub states: Vec<State>,
+}
+
+/// Structured reading of a localized entry's "extra data" string.
+///
+/// The format doesn't define this field, so the recognized patterns are conventions
+/// seen in real files:
+///
+/// - **Office phrases**: sections separated by `^^`, alternatives by `~~`. The first
+///   section holds greetings, the second reminders (as in Clippit).
+/// - **Version**: dot-separated digits only, e.g. `3.0.7` (as in Bonzi).
+/// - **URL**: starts with `http://`, `https://` or `www.`.
+///
+/// Anything else is kept as `Raw`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtraData {
+    OfficePhrases {
+        greetings: Vec<String>,
+        reminders: Vec<String>,
+    },
+    Version(String),
+    Url(String),
+    Raw(String),
+}
+
+impl ExtraData {
+    /// Classify an extra data string. Returns `None` if it's empty or whitespace.
+    pub fn parse(s: &str) -> Option<ExtraData> {
+        let s = s.trim();
+        if s.is_empty() {
+            return None;
+        }
+
+        if s.contains("^^") || s.contains("~~") {
+            let mut sections = s.split("^^").map(|section| {
+                section
+                    .split("~~")
+                    .map(str::trim)
+                    .filter(|phrase| !phrase.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            });
+            return Some(ExtraData::OfficePhrases {
+                greetings: sections.next().unwrap_or_default(),
+                reminders: sections.next().unwrap_or_default(),
+            });
+        }
+
+        let is_version = s.split('.').count() > 1
+            && s.split('.')
+                .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+        if is_version {
+            return Some(ExtraData::Version(s.to_string()));
+        }
+
+        let lower = s.to_ascii_lowercase();
+        if ["http://", "https://", "www."]
+            .iter()
+            .any(|prefix| lower.starts_with(prefix))
+        {
+            return Some(ExtraData::Url(s.to_string()));
+        }
+
+        Some(ExtraData::Raw(s.to_string()))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Sound {
     /// Raw WAV data
@@ -176,34 +611,158 @@ pub struct Sound {
 
 /// A character state grouping animations.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct State {
     pub name: String,
     pub animations: Vec<String>,
 }
 
+/// The bytes an `Acs` parses from: an owned buffer or, with the `mmap` feature, a
+/// read-only file mapping.
+///
+/// Clones share the bytes, so `Acs` can hand out slices while its reader holds them too.
+#[derive(Clone)]
+enum Backing {
+    Owned(Arc<Vec<u8>>),
+    #[cfg(feature = "mmap")]
+    Mapped(Arc<memmap2::Mmap>),
+}
+
+impl std::ops::Deref for Backing {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Owned(data) => data,
+            #[cfg(feature = "mmap")]
+            Self::Mapped(map) => map,
+        }
+    }
+}
+
+impl AsRef<[u8]> for Backing {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
 struct AnimationCacheEntry {
     name: String,
     offset: u32,
     cached: Option<Animation>,
+    timeline: Option<Timeline>,
+}
+
+/// The animation, image and audio tables. `Acs::new` reads these up front, while
+/// `Acs::new_lazy` defers them until first use.
+#[derive(Default)]
+struct Lists {
+    animation_list: Vec<AnimationCacheEntry>,
+    image_list: Vec<ImageEntry>,
+    audio_list: Vec<AudioEntry>,
+    load_warnings: Vec<AcsError>,
+}
+
+impl Lists {
+    fn read(reader: &mut AcsReader<Backing>, header: &AcsHeader) -> Result<Self, AcsError> {
+        let raw_animations = reader.read_animation_list(&header.animation_info)?;
+        let animation_list: Vec<AnimationCacheEntry> = raw_animations
+            .into_iter()
+            .map(|entry| AnimationCacheEntry {
+                name: entry.name,
+                offset: entry.locator.offset,
+                cached: None,
+                timeline: None,
+            })
+            .collect();
+
+        // Image and audio lists aren't needed to browse animations, so a corrupt list is
+        // recorded as a warning rather than failing the whole file
+        let mut load_warnings = Vec::new();
+
+        let image_list = reader
+            .read_image_list(&header.image_info)
+            .unwrap_or_else(|e| {
+                load_warnings.push(AcsError::Reader(e));
+                Vec::new()
+            });
+
+        let audio_list = reader
+            .read_audio_list(&header.audio_info)
+            .unwrap_or_else(|e| {
+                load_warnings.push(AcsError::Reader(e));
+                Vec::new()
+            });
+
+        Ok(Self {
+            animation_list,
+            image_list,
+            audio_list,
+            load_warnings,
+        })
+    }
 }
 
 pub struct Acs {
-    data: Vec<u8>,
-    #[allow(dead_code)]
+    data: Backing,
+    /// The one reader over `data`, shared by every `&self` method (see `with_reader`)
+    reader: Mutex<AcsReader<Backing>>,
     header: AcsHeader,
     character_info: CharacterInfo,
-    #[allow(dead_code)]
     raw_character_info: RawCharacterInfo,
-    animation_list: Vec<AnimationCacheEntry>,
-    image_list: Vec<ImageEntry>,
-    audio_list: Vec<AudioEntry>,
+    lists: OnceLock<Lists>,
+    image_cache: HashMap<usize, Image>,
     states: Vec<State>,
 }
 
 impl Acs {
     /// Parse an ACS file from a byte buffer.
     pub fn new(data: Vec<u8>) -> Result<Self, AcsError> {
-        let mut reader = AcsReader::new(&data);
+        Self::with_parse_mode(data, ParseMode::default())
+    }
+
+    /// Parse an ACS file with the given treatment of unknown fields.
+    ///
+    /// The mode applies to every later read too, so under `ParseMode::Strict` loading an
+    /// animation with an unexpected overlay byte fails rather than guessing.
+    pub fn with_parse_mode(data: Vec<u8>, mode: ParseMode) -> Result<Self, AcsError> {
+        Self::from_backing(Backing::Owned(Arc::new(data)), false, mode)
+    }
+
+    /// Parse only the header and character info, deferring the animation, image and
+    /// audio lists until they're first needed.
+    ///
+    /// Metadata such as `character_info()` and `states()` is available immediately. The
+    /// first call that needs a list (animation lookups, `image`, `sound`, counts or
+    /// `load_warnings`) reads all three. Unlike `new`, a corrupt animation list isn't
+    /// fatal: it's recorded in `load_warnings` and the character has no animations.
+    pub fn new_lazy(data: Vec<u8>) -> Result<Self, AcsError> {
+        Self::new_lazy_with_parse_mode(data, ParseMode::default())
+    }
+
+    /// Like `new_lazy`, with the given treatment of unknown fields (see
+    /// `with_parse_mode`), including when the lists are read.
+    pub fn new_lazy_with_parse_mode(data: Vec<u8>, mode: ParseMode) -> Result<Self, AcsError> {
+        Self::from_backing(Backing::Owned(Arc::new(data)), true, mode)
+    }
+
+    /// Parse an ACS file through a read-only memory map instead of reading it into memory.
+    ///
+    /// # Safety
+    ///
+    /// The mapping reflects the file on disk, so the file must not be modified or
+    /// truncated, by this or any other process, while the returned `Acs` is alive.
+    /// Doing so is undefined behaviour.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn from_mmap(path: impl AsRef<Path>) -> Result<Self, AcsError> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the caller guarantees the file isn't modified while mapped
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Self::from_backing(Backing::Mapped(Arc::new(map)), false, ParseMode::default())
+    }
+
+    fn from_backing(data: Backing, lazy: bool, mode: ParseMode) -> Result<Self, AcsError> {
+        let mut reader = AcsReader::with_mode(data.clone(), mode);
 
         let header = reader.read_header()?;
 
@@ -232,19 +791,11 @@ impl Acs {
             voice_info: raw_character_info.voice_info.clone(),
         };
 
-        let raw_animations = reader.read_animation_list(&header.animation_info)?;
-        let animation_list: Vec<AnimationCacheEntry> = raw_animations
-            .into_iter()
-            .map(|entry| AnimationCacheEntry {
-                name: entry.name,
-                offset: entry.locator.offset,
-                cached: None,
-            })
-            .collect();
-
-        let image_list = reader.read_image_list(&header.image_info)?;
-
-        let audio_list = reader.read_audio_list(&header.audio_info)?;
+        let lists = if lazy {
+            OnceLock::new()
+        } else {
+            OnceLock::from(Lists::read(&mut reader, &header)?)
+        };
 
         // Convert states from raw format
         let states: Vec<State> = raw_character_info
@@ -258,158 +809,824 @@ impl Acs {
 
         Ok(Self {
             data,
+            reader: Mutex::new(reader),
             header,
             character_info,
             raw_character_info,
-            animation_list,
-            image_list,
-            audio_list,
+            lists,
+            image_cache: HashMap::new(),
             states,
         })
     }
 
+    /// Read and parse an ACS file from disk.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, AcsError> {
+        Self::new(std::fs::read(path)?)
+    }
+
     /// Get character metadata.
     pub fn character_info(&self) -> &CharacterInfo {
         &self.character_info
     }
 
-    /// List all animation names.
-    pub fn animation_names(&self) -> Vec<&str> {
-        self.animation_list
-            .iter()
-            .map(|e| e.name.as_str())
-            .collect()
+    /// Recoverable problems encountered while parsing.
+    ///
+    /// If the image or audio list is corrupt, `Acs::new` still succeeds with that list
+    /// empty and records the error here. Header and character info errors remain fatal.
+    /// Out-of-range exit branches are added as each animation is loaded into the cache.
+    pub fn load_warnings(&self) -> &[AcsError] {
+        &self.lists().load_warnings
     }
 
-    /// Get all states (animation groupings).
-    pub fn states(&self) -> &[State] {
-        &self.states
+    /// Run `read` with the file's reader.
+    ///
+    /// `Acs` methods take `&self`, so the reader sits behind a lock, held only for the
+    /// call. `read` mustn't call back into methods that read (`lists()` included), so
+    /// look up offsets first. Each call seeks to what it reads rather than continuing
+    /// from the last, so a poisoned lock (a read that panicked) is still safe to use.
+    fn with_reader<T>(&self, read: impl FnOnce(&mut AcsReader<Backing>) -> T) -> T {
+        let mut reader = self.reader.lock().unwrap_or_else(PoisonError::into_inner);
+        read(&mut reader)
     }
 
-    /// Get animation by name (lazy load).
-    pub fn animation(&mut self, name: &str) -> Result<&Animation, AcsError> {
-        let idx = self
-            .animation_list
-            .iter()
-            .position(|e| e.name.eq_ignore_ascii_case(name))
-            .ok_or_else(|| AcsError::AnimationNotFound(name.to_string()))?;
+    fn lists(&self) -> &Lists {
+        self.lists.get_or_init(|| {
+            let lists = self.with_reader(|reader| Lists::read(reader, &self.header));
+            lists.unwrap_or_else(|e| Lists {
+                load_warnings: vec![e],
+                ..Lists::default()
+            })
+        })
+    }
+
+    fn lists_mut(&mut self) -> &mut Lists {
+        self.lists();
+        self.lists.get_mut().expect("lists initialized above")
+    }
 
-        if self.animation_list[idx].cached.is_some() {
-            return Ok(self.animation_list[idx].cached.as_ref().unwrap());
+    /// Get all character metadata at once, including the balloon, flags, versions and
+    /// every localized entry.
+    pub fn character_info_full(&self) -> FullCharacterInfo {
+        let raw = &self.raw_character_info;
+        let info = &self.character_info;
+
+        FullCharacterInfo {
+            name: info.name.clone(),
+            description: info.description.clone(),
+            guid: info.guid,
+            width: info.width,
+            height: info.height,
+            transparent_color: info.transparent_color,
+            palette: info.palette.clone(),
+            format_version: self.format_version(),
+            anim_set_version: self.anim_set_version(),
+            flags: raw.flags,
+            voice_info: info.voice_info.clone(),
+            balloon_info: raw.balloon_info.clone(),
+            localized_info: raw.localized_info.clone(),
+            tray_icon: raw.tray_icon.clone(),
+            states: self.states.clone(),
         }
+    }
 
-        // Load the animation
-        let offset = self.animation_list[idx].offset;
-        let mut reader = AcsReader::new(&self.data);
-        let raw = reader.read_animation_info(offset)?;
+    /// Get the word balloon's size, colors and font, without cloning the rest of
+    /// `character_info_full`. `None` if the character has no balloon info.
+    pub fn balloon_info(&self) -> Option<&BalloonInfo> {
+        self.raw_character_info.balloon_info.as_ref()
+    }
 
-        let animation = self.convert_animation(&raw);
-        self.animation_list[idx].cached = Some(animation);
+    /// Get the ACS format version as `(major, minor)`.
+    pub fn format_version(&self) -> (u16, u16) {
+        (
+            self.raw_character_info.major_version,
+            self.raw_character_info.minor_version,
+        )
+    }
 
-        Ok(self.animation_list[idx].cached.as_ref().unwrap())
+    /// Get the animation set version as `(major, minor)`.
+    pub fn anim_set_version(&self) -> (u16, u16) {
+        (
+            self.raw_character_info.anim_set_major_version,
+            self.raw_character_info.anim_set_minor_version,
+        )
     }
 
-    fn convert_animation(&self, raw: &RawAnimationInfo) -> Animation {
-        let frames: Vec<Frame> = raw
-            .frames
+    /// Get the localized "extra data" string for a language.
+    ///
+    /// The field's meaning isn't specified by the format. Office assistants store
+    /// greeting and reminder phrases (sections split by `^^`, alternatives by `~~`);
+    /// third-party characters often use it for a version string or author credits.
+    /// Returns `None` if the file has no entry for `lang_id`.
+    pub fn localized_extra(&self, lang_id: u16) -> Option<&str> {
+        self.raw_character_info
+            .localized_info
             .iter()
-            .map(|f| Frame {
-                images: f
-                    .images
-                    .iter()
-                    .map(|img| FrameImage {
-                        image_index: img.image_index as usize,
-                        x: img.x_offset,
-                        y: img.y_offset,
-                    })
-                    .collect(),
-                duration_ms: f.duration as u32 * 10, // Convert 1/100s to ms
-                sound_index: if f.sound_index >= 0 {
-                    Some(f.sound_index as usize)
-                } else {
-                    None
-                },
-                exit_branch: if f.exit_branch >= 0 {
-                    Some(f.exit_branch as usize)
-                } else {
-                    None
-                },
-                branches: f
-                    .branches
-                    .iter()
-                    .map(|b| Branch {
-                        frame_index: b.frame_index as usize,
-                        probability: b.probability,
-                    })
-                    .collect(),
-                overlays: f
-                    .overlays
-                    .iter()
-                    .map(|o| Overlay {
-                        overlay_type: OverlayType::from(o.overlay_type),
-                        replace_enabled: o.replace_enabled,
-                        image_index: o.image_index as usize,
-                        x: o.x_offset,
-                        y: o.y_offset,
-                        width: o.width,
-                        height: o.height,
-                    })
-                    .collect(),
-            })
-            .collect();
+            .find(|info| info.lang_id == lang_id)
+            .map(|info| info.extra_data.as_str())
+    }
 
-        Animation {
-            name: raw.name.clone(),
-            frames,
-            return_animation: if raw.return_animation.is_empty() {
-                None
-            } else {
-                Some(raw.return_animation.clone())
-            },
-            transition_type: TransitionType::from(raw.transition_type),
-        }
+    /// Best-effort structured reading of `localized_extra(lang_id)`; see [`ExtraData`].
+    ///
+    /// Returns `None` if there's no entry for `lang_id` or its extra data is empty.
+    /// Unrecognized content comes back as `ExtraData::Raw`.
+    pub fn localized_extra_parsed(&self, lang_id: u16) -> Option<ExtraData> {
+        self.localized_extra(lang_id).and_then(ExtraData::parse)
     }
 
-    /// Get the number of images in the file.
-    pub fn image_count(&self) -> usize {
-        self.image_list.len()
+    /// Compute a cheap, stable hash identifying this character, for use as a cache key.
+    ///
+    /// Covers the GUID, dimensions and animation names rather than the whole file. This is
+    /// FNV-1a, so it's stable across builds and platforms but not a cryptographic hash.
+    pub fn content_hash(&self) -> u64 {
+        let mut hash = Fnv1a::new();
+        hash.write(&self.character_info.guid);
+        hash.write(&self.character_info.width.to_le_bytes());
+        hash.write(&self.character_info.height.to_le_bytes());
+        for entry in &self.lists().animation_list {
+            hash.write(entry.name.as_bytes());
+            // Separator so ["ab", "c"] and ["a", "bc"] hash differently
+            hash.write(&[0]);
+        }
+
+        hash.finish()
     }
 
-    /// Get image by index (lazy decompress + palette apply).
-    pub fn image(&self, index: usize) -> Result<Image, AcsError> {
-        if index >= self.image_list.len() {
-            return Err(AcsError::InvalidImageIndex(index));
+    /// Hash an animation's visual structure, so structurally identical animations hash equal.
+    ///
+    /// Covers each frame's image indices, offsets and duration. Sounds, branches, overlays
+    /// and the name are ignored, so animations that only differ in those still match.
+    /// Uses the same FNV-1a hash as `content_hash`.
+    pub fn animation_fingerprint(&mut self, name: &str) -> Result<u64, AcsError> {
+        let animation = self.animation(name)?;
+
+        let mut hash = Fnv1a::new();
+        hash.write(&(animation.frames.len() as u32).to_le_bytes());
+        for frame in &animation.frames {
+            hash.write(&frame.duration_ms.to_le_bytes());
+            // Image count keeps frame boundaries from lining up differently
+            hash.write(&(frame.images.len() as u32).to_le_bytes());
+            for image in &frame.images {
+                hash.write(&(image.image_index as u32).to_le_bytes());
+                hash.write(&image.x.to_le_bytes());
+                hash.write(&image.y.to_le_bytes());
+            }
         }
 
-        let entry = &self.image_list[index];
-        let mut reader = AcsReader::new(&self.data);
-        let raw = reader.read_image_info(entry.locator.offset)?;
+        Ok(hash.finish())
+    }
+
+    /// Group animations with equal `animation_fingerprint`s.
+    ///
+    /// Only groups with two or more members are returned, each in file order. Animations
+    /// that fail to load are skipped.
+    pub fn find_duplicate_animations(&mut self) -> Vec<Vec<String>> {
+        let names: Vec<String> = self
+            .animation_names()
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let mut groups: Vec<(u64, Vec<String>)> = Vec::new();
+        for name in names {
+            let Ok(fingerprint) = self.animation_fingerprint(&name) else {
+                continue;
+            };
+            match groups.iter_mut().find(|(hash, _)| *hash == fingerprint) {
+                Some((_, group)) => group.push(name),
+                None => groups.push((fingerprint, vec![name])),
+            }
+        }
 
-        self.decode_image(&raw)
+        groups
+            .into_iter()
+            .map(|(_, group)| group)
+            .filter(|group| group.len() > 1)
+            .collect()
     }
 
-    fn decode_image(&self, raw: &RawImageInfo) -> Result<Image, AcsError> {
-        let pixel_data = if raw.is_compressed {
-            decompress(raw.data.clone())?
-        } else {
-            raw.data.clone()
+    /// Select which localized entry `character_info()` reports name and description from.
+    ///
+    /// Returns false (leaving the current selection unchanged) if the file has no entry
+    /// for `lang_id`. The first localized entry is used by default.
+    pub fn set_preferred_language(&mut self, lang_id: u16) -> bool {
+        let Some(info) = self
+            .raw_character_info
+            .localized_info
+            .iter()
+            .find(|info| info.lang_id == lang_id)
+        else {
+            return false;
         };
 
-        let row_width = (raw.width as usize + 3) & !3;
-        let _expected_size = row_width * raw.height as usize;
-
-        // ACS images are stored bottom-up, we need to flip them
-        let mut rgba = Vec::with_capacity(raw.width as usize * raw.height as usize * 4);
+        self.character_info.name = info.name.clone();
+        self.character_info.description = info.description.clone();
+        true
+    }
 
-        for y in (0..raw.height as usize).rev() {
-            for x in 0..raw.width as usize {
-                let idx = y * row_width + x;
+    /// List all animation names.
+    pub fn animation_names(&self) -> Vec<&str> {
+        self.lists()
+            .animation_list
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect()
+    }
+
+    /// Classify an animation by its name suffix (case-insensitive).
+    pub fn animation_role(&self, name: &str) -> AnimationRole {
+        let lower = name.to_lowercase();
+        if lower.ends_with("return") {
+            AnimationRole::ReturnHelper
+        } else if lower.ends_with("continued") {
+            AnimationRole::ContinuedHelper
+        } else {
+            AnimationRole::Playable
+        }
+    }
+
+    /// Names of animations suitable for direct playback, skipping Return/Continued helpers.
+    pub fn playable_animation_names(&self) -> Vec<&str> {
+        self.animation_names()
+            .into_iter()
+            .filter(|name| self.animation_role(name) == AnimationRole::Playable)
+            .collect()
+    }
+
+    /// Get all states (animation groupings).
+    pub fn states(&self) -> &[State] {
+        &self.states
+    }
+
+    /// Check whether an animation exists (case-insensitive).
+    pub fn animation_exists(&self, name: &str) -> bool {
+        self.find_animation(name).is_some()
+    }
+
+    fn find_animation(&self, name: &str) -> Option<usize> {
+        self.lists()
+            .animation_list
+            .iter()
+            .position(|e| e.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Get animation by name (lazy load).
+    pub fn animation(&mut self, name: &str) -> Result<&Animation, AcsError> {
+        let idx = self
+            .find_animation(name)
+            .ok_or_else(|| AcsError::AnimationNotFound(name.to_string()))?;
+
+        self.load_animation(idx)
+    }
+
+    fn load_animation(&mut self, idx: usize) -> Result<&Animation, AcsError> {
+        if self.lists().animation_list[idx].cached.is_some() {
+            return Ok(self.lists().animation_list[idx].cached.as_ref().unwrap());
+        }
+
+        // Load the animation
+        let offset = self.lists().animation_list[idx].offset;
+        let raw = self.with_reader(|reader| reader.read_animation_info(offset))?;
+
+        let mut warnings = Vec::new();
+        let animation = Self::convert_animation(&raw, &mut warnings);
+        let lists = self.lists_mut();
+        lists.load_warnings.extend(warnings);
+        lists.animation_list[idx].cached = Some(animation);
+
+        Ok(self.lists().animation_list[idx].cached.as_ref().unwrap())
+    }
+
+    /// Get an animation's timeline, building and caching it on first use.
+    ///
+    /// Repeated time/frame lookups (e.g. while scrubbing) then cost O(log n) each.
+    pub fn animation_timeline(&mut self, name: &str) -> Result<&Timeline, AcsError> {
+        let idx = self
+            .find_animation(name)
+            .ok_or_else(|| AcsError::AnimationNotFound(name.to_string()))?;
+
+        if self.lists().animation_list[idx].timeline.is_none() {
+            let timeline = self.load_animation(idx)?.build_timeline();
+            self.lists_mut().animation_list[idx].timeline = Some(timeline);
+        }
+        Ok(self.lists().animation_list[idx].timeline.as_ref().unwrap())
+    }
+
+    /// Load (and cache) every animation, calling `f` with each name and animation.
+    ///
+    /// Stops at the first animation that fails to parse and returns its error.
+    pub fn each_animation(&mut self, mut f: impl FnMut(&str, &Animation)) -> Result<(), AcsError> {
+        for idx in 0..self.lists().animation_list.len() {
+            self.load_animation(idx)?;
+            let entry = &self.lists().animation_list[idx];
+            f(&entry.name, entry.cached.as_ref().unwrap());
+        }
+        Ok(())
+    }
+
+    /// Like `each_animation`, but skips animations that fail to parse.
+    ///
+    /// Returns the name and error of every animation that was skipped.
+    pub fn try_each_animation(
+        &mut self,
+        mut f: impl FnMut(&str, &Animation),
+    ) -> Vec<(String, AcsError)> {
+        let mut errors = Vec::new();
+        for idx in 0..self.lists().animation_list.len() {
+            if let Err(e) = self.load_animation(idx) {
+                errors.push((self.lists().animation_list[idx].name.clone(), e));
+                continue;
+            }
+            let entry = &self.lists().animation_list[idx];
+            f(&entry.name, entry.cached.as_ref().unwrap());
+        }
+        errors
+    }
+
+    /// Get animation by name without requiring `&mut self`.
+    ///
+    /// Borrows the cached animation if it has been loaded, otherwise parses it without
+    /// caching the result.
+    pub(crate) fn animation_ref(&self, name: &str) -> Result<Cow<'_, Animation>, AcsError> {
+        let idx = self
+            .find_animation(name)
+            .ok_or_else(|| AcsError::AnimationNotFound(name.to_string()))?;
+
+        if let Some(ref cached) = self.lists().animation_list[idx].cached {
+            return Ok(Cow::Borrowed(cached));
+        }
+
+        let offset = self.lists().animation_list[idx].offset;
+        let raw = self.with_reader(|reader| reader.read_animation_info(offset))?;
+        // Warnings are recorded when the animation is loaded into the cache
+        Ok(Cow::Owned(Self::convert_animation(&raw, &mut Vec::new())))
+    }
+
+    /// Find every animation frame that references an image, via frame images or overlays.
+    ///
+    /// Animations that fail to parse are skipped and reported in `ImageUsage::failures`.
+    /// This loads (and caches) every animation in the file, so the first call is costly.
+    pub fn animations_using_image(&mut self, image_index: usize) -> ImageUsage {
+        let mut animations = Vec::new();
+        let failures = self.try_each_animation(|name, anim| {
+            let frames: Vec<usize> = anim
+                .frames
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.image_indices().any(|i| i == image_index))
+                .map(|(i, _)| i)
+                .collect();
+
+            if !frames.is_empty() {
+                animations.push((name.to_string(), frames));
+            }
+        });
+
+        ImageUsage {
+            animations,
+            failures,
+        }
+    }
+
+    /// Check whether an image could be dropped without breaking any frame or overlay.
+    ///
+    /// This is read-only analysis; use `animations_using_image` to see which frames keep
+    /// the image in use. Out-of-range indices report `false`, as does any image when an
+    /// animation fails to parse, since that animation might use it.
+    pub fn can_remove_image(&mut self, image_index: usize) -> bool {
+        if image_index >= self.image_count() {
+            return false;
+        }
+        let usage = self.animations_using_image(image_index);
+        usage.animations.is_empty() && usage.failures.is_empty()
+    }
+
+    /// Indices of sounds that no animation frame plays, in ascending order.
+    ///
+    /// Useful for auditing file size. This loads (and caches) every animation, which is
+    /// slow on large characters. Animations that fail to parse are skipped, so sounds
+    /// only they use are reported as unused.
+    pub fn unused_sounds(&mut self) -> Vec<usize> {
+        let mut used = vec![false; self.sound_count()];
+        self.try_each_animation(|_, anim| {
+            for frame in &anim.frames {
+                if let Some(used) = frame.sound_index.and_then(|i| used.get_mut(i)) {
+                    *used = true;
+                }
+            }
+        });
+
+        (0..used.len()).filter(|&i| !used[i]).collect()
+    }
+
+    /// Indices of images that no frame image or overlay references, in ascending order.
+    ///
+    /// The inverse of `animations_using_image`, done in a single pass; handy before
+    /// re-packing a slimmer character. This loads (and caches) every animation. As with
+    /// `unused_sounds`, animations that fail to parse are skipped.
+    pub fn unused_images(&mut self) -> Vec<usize> {
+        let mut used = vec![false; self.image_count()];
+        self.try_each_animation(|_, anim| {
+            for index in anim.frames.iter().flat_map(Frame::image_indices) {
+                if let Some(used) = used.get_mut(index) {
+                    *used = true;
+                }
+            }
+        });
+
+        (0..used.len()).filter(|&i| !used[i]).collect()
+    }
+
+    /// Rename an animation, updating return animations and states that refer to it.
+    ///
+    /// Every animation is loaded so that return references can be rewritten in the cache.
+    /// Fails if `new` already names a different animation.
+    pub fn rename_animation(&mut self, old: &str, new: &str) -> Result<(), AcsError> {
+        let idx = self
+            .find_animation(old)
+            .ok_or_else(|| AcsError::AnimationNotFound(old.to_string()))?;
+
+        if self
+            .find_animation(new)
+            .is_some_and(|existing| existing != idx)
+        {
+            return Err(AcsError::DuplicateAnimation(new.to_string()));
+        }
+
+        for i in 0..self.lists().animation_list.len() {
+            self.load_animation(i)?;
+        }
+
+        let old_name = std::mem::replace(
+            &mut self.lists_mut().animation_list[idx].name,
+            new.to_string(),
+        );
+
+        for entry in &mut self.lists_mut().animation_list {
+            let Some(anim) = entry.cached.as_mut() else {
+                continue;
+            };
+            if anim
+                .return_animation
+                .as_deref()
+                .is_some_and(|r| r.eq_ignore_ascii_case(&old_name))
+            {
+                anim.return_animation = Some(new.to_string());
+            }
+        }
+
+        if let Some(anim) = self.lists_mut().animation_list[idx].cached.as_mut() {
+            anim.name = new.to_string();
+        }
+
+        for state in &mut self.states {
+            for name in &mut state.animations {
+                if name.eq_ignore_ascii_case(&old_name) {
+                    *name = new.to_string();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get an animation's frame count without parsing its frames.
+    pub fn frame_count(&mut self, name: &str) -> Result<usize, AcsError> {
+        let idx = self
+            .find_animation(name)
+            .ok_or_else(|| AcsError::AnimationNotFound(name.to_string()))?;
+
+        if let Some(ref cached) = self.lists().animation_list[idx].cached {
+            return Ok(cached.frames.len());
+        }
+
+        let offset = self.lists().animation_list[idx].offset;
+        let header = self.with_reader(|reader| reader.read_animation_header(offset))?;
+        Ok(header.frame_count as usize)
+    }
+
+    /// Call `f` with each frame of an animation in order, parsing one frame at a time.
+    ///
+    /// Unlike `animation()`, nothing is cached and the frame list is never held in memory
+    /// at once, which suits single-pass consumers of very long animations. Use
+    /// `animation()` when frames need random access or will be read repeatedly. Already
+    /// cached animations are walked from the cache.
+    pub fn with_animation_frames(
+        &self,
+        name: &str,
+        mut f: impl FnMut(usize, &Frame),
+    ) -> Result<(), AcsError> {
+        let idx = self
+            .find_animation(name)
+            .ok_or_else(|| AcsError::AnimationNotFound(name.to_string()))?;
+
+        if let Some(ref cached) = self.lists().animation_list[idx].cached {
+            for (i, frame) in cached.frames.iter().enumerate() {
+                f(i, frame);
+            }
+            return Ok(());
+        }
+
+        // The reader is released between frames so `f` can use `self`, so keep track of
+        // where the next frame starts
+        let offset = self.lists().animation_list[idx].offset;
+        let (header, mut next) = self.with_reader(|reader| {
+            let header = reader.read_animation_header(offset)?;
+            Ok::<_, AcsError>((header, reader.position()))
+        })?;
+        for i in 0..header.frame_count as usize {
+            let raw = self.with_reader(|reader| {
+                reader.seek(next);
+                let raw = reader.read_frame_info()?;
+                next = reader.position();
+                Ok::<_, AcsError>(raw)
+            })?;
+            f(i, &Self::convert_frame(&raw));
+        }
+        Ok(())
+    }
+
+    /// Convert a parsed animation, dropping out-of-range exit branches into `warnings`.
+    fn convert_animation(raw: &RawAnimationInfo, warnings: &mut Vec<AcsError>) -> Animation {
+        let mut frames: Vec<Frame> = raw.frames.iter().map(Self::convert_frame).collect();
+
+        let frame_count = frames.len();
+        for (i, frame) in frames.iter_mut().enumerate() {
+            if let Some(target) = frame.exit_branch
+                && target > frame_count
+            {
+                frame.exit_branch = None;
+                warnings.push(AcsError::InvalidExitBranch {
+                    animation: raw.name.clone(),
+                    frame: i,
+                    target,
+                });
+            }
+        }
+
+        Animation {
+            name: raw.name.clone(),
+            frames,
+            return_animation: if raw.return_animation.is_empty() {
+                None
+            } else {
+                Some(raw.return_animation.clone())
+            },
+            transition_type: TransitionType::from(raw.transition_type),
+        }
+    }
+
+    fn convert_frame(f: &RawFrameInfo) -> Frame {
+        Frame {
+            images: f
+                .images
+                .iter()
+                .map(|img| FrameImage {
+                    image_index: img.image_index as usize,
+                    x: img.x_offset,
+                    y: img.y_offset,
+                })
+                .collect(),
+            duration_ms: f.duration as u32 * 10, // Convert 1/100s to ms
+            sound_index: if f.sound_index >= 0 {
+                Some(f.sound_index as usize)
+            } else {
+                None
+            },
+            exit_branch: if f.exit_branch >= 0 {
+                Some(f.exit_branch as usize)
+            } else {
+                None
+            },
+            branches: f
+                .branches
+                .iter()
+                .map(|b| Branch {
+                    frame_index: b.frame_index as usize,
+                    probability: b.probability,
+                })
+                .collect(),
+            overlays: f
+                .overlays
+                .iter()
+                .map(|o| Overlay {
+                    overlay_type: OverlayType::from(o.overlay_type),
+                    replace_enabled: o.replace_enabled,
+                    image_index: o.image_index as usize,
+                    x: o.x_offset,
+                    y: o.y_offset,
+                    width: o.width,
+                    height: o.height,
+                })
+                .collect(),
+        }
+    }
+
+    /// Decode the mouth overlay image for each overlay type used by an animation.
+    ///
+    /// Lets a lip-sync player swap mouths without decoding on every frame. When frames
+    /// use different images for the same type, the first one wins; each image is decoded
+    /// only once.
+    pub fn mouth_overlay_images(
+        &mut self,
+        animation: &str,
+    ) -> Result<HashMap<OverlayType, Image>, AcsError> {
+        let mut indices = HashMap::new();
+        for overlay in self
+            .animation(animation)?
+            .frames
+            .iter()
+            .flat_map(|f| &f.overlays)
+        {
+            indices
+                .entry(overlay.overlay_type)
+                .or_insert(overlay.image_index);
+        }
+
+        let mut decoded: HashMap<usize, Image> = HashMap::new();
+        let mut images = HashMap::with_capacity(indices.len());
+        for (overlay_type, index) in indices {
+            let image = match decoded.get(&index) {
+                Some(image) => image.clone(),
+                None => {
+                    let image = self.image(index)?;
+                    decoded.insert(index, image.clone());
+                    image
+                }
+            };
+            images.insert(overlay_type, image);
+        }
+        Ok(images)
+    }
+
+    /// Collect the distinct values of the undocumented overlay byte across all animations.
+    ///
+    /// A diagnostic for reverse-engineering the field: anything other than `{0}` means
+    /// the file uses it for something. Parses every animation without caching.
+    pub fn overlay_unknown_values(&self) -> Result<BTreeSet<u8>, AcsError> {
+        let animation_list = &self.lists().animation_list;
+        self.with_reader(|reader| {
+            let mut values = BTreeSet::new();
+            for entry in animation_list {
+                let raw = reader.read_animation_info(entry.offset)?;
+                for frame in &raw.frames {
+                    values.extend(frame.overlays.iter().map(|o| o.unknown));
+                }
+            }
+            Ok(values)
+        })
+    }
+
+    /// Get the number of images in the file.
+    pub fn image_count(&self) -> usize {
+        self.lists().image_list.len()
+    }
+
+    /// Estimate the memory needed to decode every image to RGBA (`width * height * 4` each).
+    ///
+    /// Reads every image's header (`AcsReader::read_image_header`) for its dimensions, so
+    /// it's a seek per image but nothing is copied or decompressed. Images whose header
+    /// can't be read are skipped.
+    pub fn estimated_decoded_bytes(&self) -> usize {
+        let image_list = &self.lists().image_list;
+        self.with_reader(|reader| {
+            image_list
+                .iter()
+                .filter_map(|entry| reader.read_image_header(entry.locator.offset).ok())
+                .map(|(width, height, _)| width as usize * height as usize * 4)
+                .sum()
+        })
+    }
+
+    /// Get image by index (lazy decompress + palette apply).
+    pub fn image(&self, index: usize) -> Result<Image, AcsError> {
+        self.image_with_palette(index, None)
+    }
+
+    /// Decode an image, optionally with a replacement palette (which bypasses the cache).
+    fn image_with_palette(
+        &self,
+        index: usize,
+        palette: Option<&[[u8; 4]]>,
+    ) -> Result<Image, AcsError> {
+        if index >= self.lists().image_list.len() {
+            return Err(AcsError::InvalidImageIndex(index));
+        }
+
+        if palette.is_none()
+            && let Some(cached) = self.image_cache.get(&index)
+        {
+            return Ok(cached.clone());
+        }
+
+        let offset = self.lists().image_list[index].locator.offset;
+        let raw = self.with_reader(|reader| reader.read_image_info(offset))?;
+
+        self.decode_image(&raw, palette.unwrap_or(&self.character_info.palette))
+    }
+
+    /// Decode and cache every image used by an animation's frames and overlays.
+    ///
+    /// Subsequent `image()` and `render_frame()` calls for the animation skip decompression,
+    /// avoiding a stutter on first playback. This trades memory for latency: decoded images
+    /// are kept as RGBA for the lifetime of the `Acs`.
+    pub fn preload_animation_images(&mut self, name: &str) -> Result<(), AcsError> {
+        let indices: BTreeSet<usize> = self
+            .animation(name)?
+            .frames
+            .iter()
+            .flat_map(|f| {
+                f.images
+                    .iter()
+                    .map(|img| img.image_index)
+                    .chain(f.overlays.iter().map(|o| o.image_index))
+            })
+            .collect();
+
+        for index in indices {
+            if !self.image_cache.contains_key(&index) {
+                let image = self.image(index)?;
+                self.image_cache.insert(index, image);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get image by index as an 8-bit indexed BMP using the character palette.
+    ///
+    /// BMP has no alpha, so transparent pixels keep the transparent color's palette entry.
+    pub fn image_bmp(&self, index: usize) -> Result<Vec<u8>, AcsError> {
+        let raw = self.read_raw_image(index)?;
+
+        let mut pixel_data = if raw.is_compressed {
+            decompress(raw.data)?
+        } else {
+            raw.data
+        };
+
+        // Rows are already bottom-up and DWORD aligned, matching BMP's native layout
+        let row_width = (raw.width as usize + 3) & !3;
+        pixel_data.resize(row_width * raw.height as usize, 0);
+
+        Ok(encode_bmp_indexed(
+            raw.width as u32,
+            raw.height as u32,
+            &self.character_info.palette,
+            &pixel_data,
+        ))
+    }
+
+    /// Get an image's palette indices exactly as stored, after decompression if needed.
+    ///
+    /// Rows are bottom-up and padded to `image_stride(index)` bytes, with no palette or
+    /// transparency applied. Meant for debugging decompression against a reference.
+    pub fn image_raw_indices(&self, index: usize) -> Result<Vec<u8>, AcsError> {
+        let raw = self.read_raw_image(index)?;
+        if raw.is_compressed {
+            Ok(decompress(raw.data)?)
+        } else {
+            Ok(raw.data)
+        }
+    }
+
+    /// Get the length in bytes of each row in `image_raw_indices` (width rounded up to a
+    /// multiple of 4).
+    pub fn image_stride(&self, index: usize) -> Result<usize, AcsError> {
+        let raw = self.read_raw_image(index)?;
+        Ok((raw.width as usize + 3) & !3)
+    }
+
+    fn read_raw_image(&self, index: usize) -> Result<RawImageInfo, AcsError> {
+        let entry = self
+            .lists()
+            .image_list
+            .get(index)
+            .ok_or(AcsError::InvalidImageIndex(index))?;
+        Ok(self.with_reader(|reader| reader.read_image_info(entry.locator.offset))?)
+    }
+
+    fn decode_image(&self, raw: &RawImageInfo, palette: &[[u8; 4]]) -> Result<Image, AcsError> {
+        let pixel_data = if raw.is_compressed {
+            decompress(raw.data.clone())?
+        } else {
+            raw.data.clone()
+        };
+
+        let row_width = (raw.width as usize + 3) & !3;
+        let _expected_size = row_width * raw.height as usize;
+
+        // ACS images are stored bottom-up, we need to flip them
+        let mut rgba = Vec::with_capacity(raw.width as usize * raw.height as usize * 4);
+
+        for y in (0..raw.height as usize).rev() {
+            for x in 0..raw.width as usize {
+                let idx = y * row_width + x;
                 if idx < pixel_data.len() {
                     let color_index = pixel_data[idx] as usize;
                     if color_index == self.character_info.transparent_color as usize {
                         rgba.extend_from_slice(&[0, 0, 0, 0]);
-                    } else if color_index < self.character_info.palette.len() {
-                        rgba.extend_from_slice(&self.character_info.palette[color_index]);
+                    } else if color_index < palette.len() {
+                        rgba.extend_from_slice(&palette[color_index]);
                     } else {
                         rgba.extend_from_slice(&[0, 0, 0, 255]);
                     }
@@ -428,88 +1645,1408 @@ impl Acs {
 
     /// Get the number of sounds in the file.
     pub fn sound_count(&self) -> usize {
-        self.audio_list.len()
+        self.lists().audio_list.len()
     }
 
     /// Get sound by index.
     pub fn sound(&self, index: usize) -> Result<Sound, AcsError> {
-        if index >= self.audio_list.len() {
+        if index >= self.lists().audio_list.len() {
             return Err(AcsError::InvalidSoundIndex(index));
         }
 
-        let entry = &self.audio_list[index];
-        let mut reader = AcsReader::new(&self.data);
-        let data = reader.read_audio_data(entry)?;
+        let entry = &self.lists().audio_list[index];
+        let data = self.with_reader(|reader| reader.read_audio_data(entry))?;
 
         Ok(Sound { data })
     }
 
+    /// Copy the file with one sound's WAV data replaced, e.g. by TTS output.
+    ///
+    /// The new sound and rewritten lists are appended, with the sound's entry
+    /// repointed, so everything else keeps its offset; the old sound's bytes are left in
+    /// place, unreferenced.
+    pub fn with_sound_replaced(&self, index: usize, wav: &[u8]) -> Result<Vec<u8>, AcsError> {
+        if index >= self.sound_count() {
+            return Err(AcsError::InvalidSoundIndex(index));
+        }
+
+        let appended = crate::writer::Appended {
+            replaced_sounds: &[(index, wav)],
+            ..Default::default()
+        };
+        Ok(crate::writer::append(
+            &self.data,
+            (
+                &self.animation_entries()?,
+                &self.lists().image_list,
+                &self.lists().audio_list,
+            ),
+            appended,
+        ))
+    }
+
+    /// The animation list as it'd be written out, with names changed by
+    /// `rename_animation`.
+    fn animation_entries(&self) -> Result<Vec<AnimationEntry>, AcsError> {
+        let mut entries =
+            self.with_reader(|reader| reader.read_animation_list(&self.header.animation_info))?;
+        for (entry, current) in entries.iter_mut().zip(&self.lists().animation_list) {
+            entry.name.clone_from(&current.name);
+        }
+        Ok(entries)
+    }
+
+    /// The file's bytes, including any imported animations.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Copy an animation from another character into this one, with the images and
+    /// sounds it uses.
+    ///
+    /// Images are remapped to this character's palette by nearest color, keeping the
+    /// other character's transparent pixels transparent. The return animation is kept
+    /// by name, whether or not this character has one by that name. Like
+    /// `with_sound_replaced`, the new data is appended to the file (see `as_bytes`).
+    /// Fails if an animation by that name already exists.
+    pub fn import_animation(&mut self, other: &mut Acs, name: &str) -> Result<(), AcsError> {
+        let list_name = other
+            .find_animation(name)
+            .map(|idx| other.lists().animation_list[idx].name.clone())
+            .ok_or_else(|| AcsError::AnimationNotFound(name.to_string()))?;
+        if self.find_animation(&list_name).is_some() {
+            return Err(AcsError::DuplicateAnimation(list_name));
+        }
+        let mut animation = other.animation(&list_name)?.clone();
+
+        // Number the other file's images and sounds after ours, in order of first use
+        let mut image_map: HashMap<usize, usize> = HashMap::new();
+        let mut sound_map: HashMap<usize, usize> = HashMap::new();
+        let mut images = Vec::new();
+        let mut sounds = Vec::new();
+        let palette_map = self.palette_map_from(other);
+
+        for frame in &mut animation.frames {
+            let indices = frame
+                .images
+                .iter_mut()
+                .map(|img| &mut img.image_index)
+                .chain(frame.overlays.iter_mut().map(|o| &mut o.image_index));
+            for index in indices {
+                if !image_map.contains_key(index) {
+                    image_map.insert(*index, self.image_count() + images.len());
+                    images.push(other.remapped_image(*index, &palette_map)?);
+                }
+                *index = image_map[index];
+            }
+
+            if let Some(index) = frame.sound_index.as_mut() {
+                if !sound_map.contains_key(index) {
+                    sound_map.insert(*index, self.sound_count() + sounds.len());
+                    sounds.push(other.sound(*index)?.data);
+                }
+                *index = sound_map[index];
+            }
+        }
+
+        let appended = crate::writer::Appended {
+            animation: Some((&list_name, &animation)),
+            images: &images,
+            sounds: &sounds,
+            ..Default::default()
+        };
+        let data = crate::writer::append(
+            &self.data,
+            (
+                &self.animation_entries()?,
+                &self.lists().image_list,
+                &self.lists().audio_list,
+            ),
+            appended,
+        );
+
+        let data = Backing::Owned(Arc::new(data));
+        let mode = self.with_reader(|reader| reader.mode());
+        let mut reader = AcsReader::with_mode(data.clone(), mode);
+        let header = reader.read_header()?;
+        let mut lists = Lists::read(&mut reader, &header)?;
+        // Existing animations keep their loaded (and possibly edited) copies
+        let old = std::mem::take(&mut self.lists_mut().animation_list);
+        for (new, old) in lists.animation_list.iter_mut().zip(old) {
+            new.cached = old.cached;
+            new.timeline = old.timeline;
+        }
+
+        self.data = data;
+        self.reader = Mutex::new(reader);
+        self.header = header;
+        self.lists = OnceLock::from(lists);
+        Ok(())
+    }
+
+    /// Map each of `other`'s palette indices to the nearest color in this palette.
+    fn palette_map_from(&self, other: &Acs) -> [u8; 256] {
+        let ours = &self.character_info.palette;
+        let transparent = self.character_info.transparent_color;
+
+        std::array::from_fn(|i| {
+            if i == other.character_info.transparent_color as usize {
+                return transparent;
+            }
+            let [r, g, b, _] = other
+                .character_info
+                .palette
+                .get(i)
+                .copied()
+                .unwrap_or([0, 0, 0, 255]);
+            let distance = |c: &[u8; 4]| {
+                let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2);
+                d(c[0], r) + d(c[1], g) + d(c[2], b)
+            };
+            (0..ours.len())
+                .filter(|&j| j != transparent as usize)
+                .min_by_key(|&j| distance(&ours[j]))
+                .map_or(transparent, |j| j as u8)
+        })
+    }
+
+    /// An image's pixels run through a palette map, top-down and unpadded.
+    fn remapped_image(
+        &self,
+        index: usize,
+        palette_map: &[u8; 256],
+    ) -> Result<crate::writer::BuilderImage, AcsError> {
+        let raw = self.read_raw_image(index)?;
+        let (width, height) = (raw.width as usize, raw.height as usize);
+        let mut pixels = if raw.is_compressed {
+            decompress(raw.data)?
+        } else {
+            raw.data
+        };
+        let stride = (width + 3) & !3;
+        pixels.resize(stride * height, self.character_info.transparent_color);
+
+        let indices = pixels
+            .chunks_exact(stride.max(1))
+            .rev()
+            .flat_map(|row| row[..width].iter().map(|&p| palette_map[p as usize]))
+            .collect();
+        Ok(crate::writer::BuilderImage {
+            width: raw.width,
+            height: raw.height,
+            indices,
+        })
+    }
+
     /// Render a complete animation frame by compositing all frame images.
     pub fn render_frame(
         &self,
         animation_name: &str,
         frame_index: usize,
     ) -> Result<Image, AcsError> {
-        let anim_idx = self
-            .animation_list
+        self.render_frame_with_options(animation_name, frame_index, &RenderOptions::default())
+    }
+
+    /// Render a complete animation frame, applying the given render options.
+    pub fn render_frame_with_options(
+        &self,
+        animation_name: &str,
+        frame_index: usize,
+        options: &RenderOptions,
+    ) -> Result<Image, AcsError> {
+        if let Some(ref palette) = options.palette_override
+            && (palette.len() < self.character_info.palette.len() || palette.len() > 256)
+        {
+            return Err(AcsError::InvalidPaletteLength(palette.len()));
+        }
+
+        let animation = self.animation_ref(animation_name)?;
+        let frame =
+            animation
+                .frames
+                .get(frame_index)
+                .ok_or_else(|| AcsError::InvalidFrameIndex {
+                    animation: animation_name.to_string(),
+                    index: frame_index,
+                })?;
+
+        options.apply(self.composite_frame(frame, options)?)
+    }
+
+    /// Render a complete animation frame and encode it as PNG bytes.
+    #[cfg(feature = "png")]
+    pub fn render_frame_png(
+        &mut self,
+        animation: &str,
+        frame_index: usize,
+    ) -> Result<Vec<u8>, AcsError> {
+        // Load through the cache so exporting every frame doesn't re-parse the animation
+        self.animation(animation)?;
+        self.render_frame(animation, frame_index)?.to_png()
+    }
+
+    /// Render the frame shown `t_ms` into an animation, played linearly from frame 0.
+    ///
+    /// Branches are ignored and times past the end show the last frame. With `interpolate`,
+    /// the frame is cross-faded into the next one in proportion to the time elapsed within
+    /// it, for smoother playback on high refresh-rate displays. Agent never blended frames,
+    /// so interpolated output is not authentic.
+    pub fn render_at_time(
+        &mut self,
+        animation: &str,
+        t_ms: u32,
+        interpolate: bool,
+    ) -> Result<Image, AcsError> {
+        let timeline = self.animation_timeline(animation)?;
+        if timeline.is_empty() {
+            return Err(AcsError::InvalidFrameIndex {
+                animation: animation.to_string(),
+                index: 0,
+            });
+        }
+
+        let index = timeline.frame_at(t_ms);
+        let frame_count = timeline.len();
+        let duration = timeline.duration_of(index);
+        let elapsed = t_ms.saturating_sub(timeline.start_of(index));
+
+        let current = self.render_frame(animation, index)?;
+        if !interpolate || elapsed == 0 || elapsed >= duration || index + 1 >= frame_count {
+            return Ok(current);
+        }
+
+        let next = self.render_frame(animation, index + 1)?;
+        Ok(current.blend(&next, elapsed as f32 / duration as f32))
+    }
+
+    fn composite_frame(&self, frame: &Frame, options: &RenderOptions) -> Result<Image, AcsError> {
+        let width = self.character_info.width as u32;
+        let height = self.character_info.height as u32;
+
+        let mut image = Image {
+            width,
+            height,
+            data: vec![0u8; (width * height * 4) as usize],
+        };
+
+        // Frame images are listed front to back, so by default they're drawn in reverse
+        // to leave the first image on top
+        let images: Box<dyn Iterator<Item = &FrameImage>> = match options.image_order {
+            ImageOrder::AsStored => Box::new(frame.images.iter()),
+            ImageOrder::Reversed => Box::new(frame.images.iter().rev()),
+        };
+
+        // The selected mouth overlay, if the frame has one, goes on top of everything
+        let mouth = options.mouth.and_then(|mouth| {
+            frame
+                .overlays
+                .iter()
+                .find(|overlay| overlay.overlay_type == mouth)
+        });
+        let layers = images
+            .map(|i| (i.image_index, i.x, i.y))
+            .chain(mouth.map(|o| (o.image_index, o.x, o.y)));
+
+        let mut placed = Vec::new();
+        for (image_index, x, y) in layers {
+            let img = self.image_with_palette(image_index, options.palette_override.as_deref())?;
+            if options.debug_overlay {
+                placed.push((x as i64, y as i64, img.width, img.height));
+            }
+            image.blit(&img, x as i64, y as i64);
+        }
+
+        if options.debug_overlay {
+            image.draw_debug_overlay(&placed);
+        }
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(name: &str) -> Acs {
+        let path = format!("{}/../notes/files/{}", env!("CARGO_MANIFEST_DIR"), name);
+        Acs::new(std::fs::read(path).expect("read test file")).expect("parse test file")
+    }
+
+    #[test]
+    fn test_animation_exists() {
+        let acs = load("Bonzi.acs");
+        assert!(acs.animation_exists("Explain"));
+        assert!(acs.animation_exists("explain"));
+        assert!(!acs.animation_exists("NotARealAnimation"));
+    }
+
+    #[test]
+    fn test_transparent_rgba() {
+        let acs = load("Bonzi.acs");
+        let info = acs.character_info();
+        assert_eq!(info.transparent_color, 254);
+        assert_eq!(info.transparent_rgba(), Some([0, 255, 255, 255]));
+
+        let mut info = info.clone();
+        info.palette.truncate(10);
+        assert_eq!(info.transparent_rgba(), None);
+    }
+
+    #[test]
+    fn test_versions() {
+        let acs = load("Bonzi.acs");
+        let format = acs.format_version();
+        let anim_set = acs.anim_set_version();
+        println!(
+            "Bonzi format version: {:?}, anim set version: {:?}",
+            format, anim_set
+        );
+        assert_ne!(format, (0, 0));
+        assert_ne!(anim_set, (0, 0));
+    }
+
+    #[test]
+    fn test_set_preferred_language() {
+        let mut acs = load("clippit.acs");
+        assert!(acs.set_preferred_language(1031));
+        assert_eq!(acs.character_info().name, "Karl Klammer");
+        assert!(acs.set_preferred_language(9));
+        assert_eq!(acs.character_info().name, "Clippit");
+        assert!(!acs.set_preferred_language(0xFFFF));
+        assert_eq!(acs.character_info().name, "Clippit");
+    }
+
+    #[test]
+    fn test_preload_animation_images() {
+        let mut acs = load("Bonzi.acs");
+        acs.preload_animation_images("Explain").unwrap();
+
+        let anim = acs.animation("Explain").unwrap().clone();
+        for frame in &anim.frames {
+            for img in &frame.images {
+                assert!(acs.image_cache.contains_key(&img.image_index));
+            }
+            for overlay in &frame.overlays {
+                assert!(acs.image_cache.contains_key(&overlay.image_index));
+            }
+        }
+
+        let cached = acs.render_frame("Explain", 0).unwrap();
+        acs.image_cache.clear();
+        let uncached = acs.render_frame("Explain", 0).unwrap();
+        assert_eq!(cached.data, uncached.data);
+    }
+
+    #[test]
+    fn test_frame_count() {
+        let mut acs = load("Bonzi.acs");
+        let names: Vec<String> = acs
+            .animation_names()
             .iter()
-            .position(|e| e.name.eq_ignore_ascii_case(animation_name))
-            .ok_or_else(|| AcsError::AnimationNotFound(animation_name.to_string()))?;
+            .map(|s| s.to_string())
+            .collect();
+        for name in names {
+            let count = acs.frame_count(&name).unwrap();
+            assert_eq!(count, acs.animation(&name).unwrap().frames.len());
+            assert_eq!(count, acs.frame_count(&name).unwrap());
+        }
+        assert!(acs.frame_count("NotARealAnimation").is_err());
+    }
 
-        let frame = if let Some(ref cached) = self.animation_list[anim_idx].cached {
-            cached.frames.get(frame_index)
-        } else {
-            let offset = self.animation_list[anim_idx].offset;
-            let mut reader = AcsReader::new(&self.data);
-            let raw = reader.read_animation_info(offset)?;
-            let animation = self.convert_animation(&raw);
+    #[test]
+    fn test_render_frame_premultiplied() {
+        let acs = load("Bonzi.acs");
+        let options = RenderOptions {
+            premultiply: true,
+            ..Default::default()
+        };
+        let straight = acs.render_frame("Explain", 0).unwrap();
+        let premultiplied = acs
+            .render_frame_with_options("Explain", 0, &options)
+            .unwrap();
+        assert_eq!(premultiplied.data, straight.premultiply().data);
+    }
 
-            if frame_index < animation.frames.len() {
-                return self.composite_frame(&animation.frames[frame_index]);
-            } else {
-                return Err(AcsError::InvalidImageIndex(frame_index));
+    #[test]
+    fn test_localized_extra() {
+        let acs = load("Bonzi.acs");
+        assert_eq!(acs.localized_extra(9), Some("3.0.7"));
+        assert_eq!(acs.localized_extra(0xFFFF), None);
+    }
+
+    #[test]
+    fn test_localized_extra_parsed() {
+        assert_eq!(load("pikachu.acs").localized_extra_parsed(9), None);
+        assert_eq!(
+            load("Bonzi.acs").localized_extra_parsed(9),
+            Some(ExtraData::Version("3.0.7".to_string()))
+        );
+
+        let Some(ExtraData::OfficePhrases {
+            greetings,
+            reminders,
+        }) = load("clippit.acs").localized_extra_parsed(9)
+        else {
+            panic!("expected Office phrases");
+        };
+        assert!(!greetings.is_empty());
+        assert!(!reminders.is_empty());
+
+        assert_eq!(
+            ExtraData::parse("Character by someone"),
+            Some(ExtraData::Raw("Character by someone".to_string()))
+        );
+        assert_eq!(
+            ExtraData::parse("https://example.com/agent"),
+            Some(ExtraData::Url("https://example.com/agent".to_string()))
+        );
+        assert_eq!(ExtraData::parse("  "), None);
+    }
+
+    #[test]
+    fn test_duration_secs() {
+        let frame = Frame {
+            images: Vec::new(),
+            duration_ms: 100,
+            sound_index: None,
+            exit_branch: None,
+            branches: Vec::new(),
+            overlays: Vec::new(),
+        };
+        assert_eq!(frame.duration_secs(), 0.1);
+
+        let animation = Animation {
+            name: "Test".to_string(),
+            frames: vec![frame.clone(), frame.clone(), frame],
+            return_animation: None,
+            transition_type: TransitionType::None,
+        };
+        assert_eq!(animation.total_duration_ms(), 300);
+        assert!((animation.total_duration_secs() - 0.3).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_total_duration_saturates() {
+        let frame = Frame {
+            images: Vec::new(),
+            duration_ms: u32::MAX / 2 + 1,
+            sound_index: None,
+            exit_branch: None,
+            branches: Vec::new(),
+            overlays: Vec::new(),
+        };
+        let animation = Animation {
+            name: "Test".to_string(),
+            frames: vec![frame.clone(), frame.clone(), frame],
+            return_animation: None,
+            transition_type: TransitionType::None,
+        };
+        assert_eq!(animation.total_duration_ms(), u32::MAX);
+    }
+
+    #[test]
+    fn test_render_frame_palette_override() {
+        let acs = load("Bonzi.acs");
+        let grayscale: Vec<[u8; 4]> = (0..=255u8).map(|i| [i, i, i, 255]).collect();
+        let options = RenderOptions {
+            palette_override: Some(grayscale),
+            ..Default::default()
+        };
+
+        let original = acs.render_frame("Explain", 0).unwrap();
+        let recolored = acs
+            .render_frame_with_options("Explain", 0, &options)
+            .unwrap();
+        assert_ne!(original.data, recolored.data);
+
+        for (before, after) in original.data.chunks(4).zip(recolored.data.chunks(4)) {
+            // Transparency is unchanged, opaque pixels are now gray
+            assert_eq!(before[3], after[3]);
+            assert_eq!(after[0], after[1]);
+            assert_eq!(after[1], after[2]);
+        }
+
+        let short = RenderOptions {
+            palette_override: Some(vec![[0, 0, 0, 255]; 4]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            acs.render_frame_with_options("Explain", 0, &short),
+            Err(AcsError::InvalidPaletteLength(4))
+        ));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let bonzi = load("Bonzi.acs");
+        assert_eq!(bonzi.content_hash(), load("Bonzi.acs").content_hash());
+        assert_ne!(bonzi.content_hash(), load("clippit.acs").content_hash());
+    }
+
+    #[test]
+    fn test_animation_fingerprint() {
+        let mut acs = load("Bonzi.acs");
+        let greet = acs.animation_fingerprint("Greet").unwrap();
+        assert_eq!(acs.animation_fingerprint("Greet").unwrap(), greet);
+        assert_ne!(acs.animation_fingerprint("RestPose").unwrap(), greet);
+        assert!(acs.animation_fingerprint("NoSuchAnimation").is_err());
+
+        let duplicates = acs.find_duplicate_animations();
+        assert!(duplicates.iter().all(|group| group.len() > 1));
+        assert!(
+            duplicates
+                .iter()
+                .any(|group| group.contains(&"Idle1_1".to_string())
+                    && group.contains(&"Idle1_1 (2)".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_missing_audio_section() {
+        let path = format!("{}/../notes/files/Bonzi.acs", env!("CARGO_MANIFEST_DIR"));
+        let mut data = std::fs::read(path).unwrap();
+        // Zero the audio locator (last of the four header locators)
+        data[28..36].fill(0);
+
+        let acs = Acs::new(data).unwrap();
+        assert_eq!(acs.sound_count(), 0);
+        assert!(acs.sound(0).is_err());
+        assert!(acs.image_count() > 0);
+    }
+
+    #[test]
+    fn test_mouth_overlay_images() {
+        let mut acs = load("Bonzi.acs");
+        let mouths = acs.mouth_overlay_images("Decline").unwrap();
+        assert!(mouths.len() > 1);
+        assert!(mouths.contains_key(&OverlayType::MouthClosed));
+        assert!(mouths.contains_key(&OverlayType::MouthWide4));
+
+        let overlay = acs.animation("Decline").unwrap().frames[0]
+            .overlays
+            .iter()
+            .find(|o| o.overlay_type == OverlayType::MouthClosed)
+            .unwrap()
+            .image_index;
+        assert_eq!(
+            mouths[&OverlayType::MouthClosed].data,
+            acs.image(overlay).unwrap().data
+        );
+    }
+
+    #[test]
+    fn test_overlay_unknown_values() {
+        let acs = load("Bonzi.acs");
+        let values = acs.overlay_unknown_values().unwrap();
+        println!("Bonzi overlay unknown byte values: {:?}", values);
+        assert!(!values.is_empty());
+    }
+
+    #[test]
+    fn test_animations_using_image() {
+        let mut acs = load("Bonzi.acs");
+        let rest_image = acs.animation("RestPose").unwrap().frames[0].images[0].image_index;
+
+        let usage = acs.animations_using_image(rest_image);
+        assert!(usage.failures.is_empty());
+        assert!(usage.animations.len() > 1);
+        assert!(
+            usage
+                .animations
+                .iter()
+                .any(|(name, frames)| name == "RestPose" && frames == &[0])
+        );
+
+        let overlay_image = acs.animation("RestPose").unwrap().frames[0].overlays[0].image_index;
+        assert!(
+            !acs.animations_using_image(overlay_image)
+                .animations
+                .is_empty()
+        );
+        assert!(acs.animations_using_image(usize::MAX).animations.is_empty());
+    }
+
+    #[test]
+    fn test_animations_using_image_reports_failures() {
+        let path = format!("{}/../notes/files/Bonzi.acs", env!("CARGO_MANIFEST_DIR"));
+        let mut data = std::fs::read(path).unwrap();
+        // Point the first animation's locator past the end of the file
+        let header = AcsReader::new(&data).read_header().unwrap();
+        let entry = header.animation_info.offset as usize + 4;
+        let name_len = u32::from_le_bytes(data[entry..entry + 4].try_into().unwrap()) as usize;
+        let locator = entry + 4 + (name_len + 1) * 2;
+        let bad_offset = data.len() as u32 + 1000;
+        data[locator..locator + 4].copy_from_slice(&bad_offset.to_le_bytes());
+
+        let mut acs = Acs::new(data).unwrap();
+        let broken = acs.animation_names()[0].to_string();
+        let failures = acs.animations_using_image(0).failures;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, broken);
+        assert!(!acs.can_remove_image(acs.image_count() - 1));
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_trace_logging() {
+        use std::sync::Mutex;
+
+        struct Capture(Mutex<Vec<String>>);
+
+        impl log::Log for Capture {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record) {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+
+            fn flush(&self) {}
+        }
+
+        static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+        log::set_logger(&CAPTURE).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        load("Bonzi.acs");
+        let lines = CAPTURE.0.lock().unwrap();
+        assert!(lines.iter().any(|l| l.starts_with("header:")));
+        assert!(lines.iter().any(|l| l.starts_with("animation list")));
+    }
+
+    #[test]
+    fn test_from_path() {
+        let path = format!("{}/../notes/files/Bonzi.acs", env!("CARGO_MANIFEST_DIR"));
+        let acs = Acs::from_path(path).unwrap();
+        assert_eq!(acs.character_info().width, 200);
+
+        let missing = Acs::from_path("does/not/exist.acs");
+        assert!(matches!(missing, Err(AcsError::Io(_))));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_from_mmap() {
+        let path = format!("{}/../notes/files/Bonzi.acs", env!("CARGO_MANIFEST_DIR"));
+        // SAFETY: the test fixture isn't modified while the test runs
+        let mapped = unsafe { Acs::from_mmap(&path) }.unwrap();
+        let read = Acs::from_path(&path).unwrap();
+
+        assert_eq!(mapped.content_hash(), read.content_hash());
+        assert_eq!(mapped.animation_names(), read.animation_names());
+        assert_eq!(mapped.image(0).unwrap().data, read.image(0).unwrap().data);
+        assert_eq!(mapped.sound(0).unwrap().data, read.sound(0).unwrap().data);
+
+        let a = mapped.render_frame("Explain", 0).unwrap();
+        let b = read.render_frame("Explain", 0).unwrap();
+        assert_eq!(a.data, b.data);
+    }
+
+    #[test]
+    fn test_image_raw_indices() {
+        let acs = load("Bonzi.acs");
+        let image = acs.image(0).unwrap();
+        let indices = acs.image_raw_indices(0).unwrap();
+        let stride = acs.image_stride(0).unwrap();
+
+        assert_eq!(stride % 4, 0);
+        assert!(stride >= image.width as usize);
+        assert_eq!(indices.len(), stride * image.height as usize);
+        assert!(matches!(
+            acs.image_raw_indices(usize::MAX),
+            Err(AcsError::InvalidImageIndex(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_invalid_frame_index() {
+        let acs = load("Bonzi.acs");
+        let err = acs.render_frame("Greet", 9999).unwrap_err();
+        assert!(matches!(
+            &err,
+            AcsError::InvalidFrameIndex { animation, index: 9999 } if animation == "Greet"
+        ));
+        assert_eq!(err.to_string(), "Greet: invalid frame index 9999");
+    }
+
+    #[test]
+    fn test_render_at_time() {
+        let mut acs = load("Bonzi.acs");
+        let frames = &acs.animation("Explain").unwrap().frames;
+        let (first, last) = (frames[0].duration_ms, frames.len() - 1);
+        assert!(first > 0);
+
+        let frame0 = acs.render_frame("Explain", 0).unwrap();
+        let frame1 = acs.render_frame("Explain", 1).unwrap();
+        assert_eq!(
+            acs.render_at_time("Explain", 0, true).unwrap().data,
+            frame0.data
+        );
+        assert_eq!(
+            acs.render_at_time("Explain", first, true).unwrap().data,
+            frame1.data
+        );
+        assert_eq!(
+            acs.render_at_time("Explain", first / 2, false)
+                .unwrap()
+                .data,
+            frame0.data
+        );
+        assert_eq!(
+            acs.render_at_time("Explain", u32::MAX, true).unwrap().data,
+            acs.render_frame("Explain", last).unwrap().data
+        );
+    }
+
+    #[test]
+    fn test_guid_string() {
+        let acs = load("Bonzi.acs");
+        let guid = acs.character_info().guid_string();
+        assert_eq!(guid.len(), 38);
+        assert!(guid.starts_with('{') && guid.ends_with('}'));
+        let groups: Vec<usize> = guid[1..37].split('-').map(|g| g.len()).collect();
+        assert_eq!(groups, [8, 4, 4, 4, 12]);
+        assert!(
+            guid[1..37]
+                .chars()
+                .all(|c| c == '-' || c.is_ascii_hexdigit())
+        );
+    }
+
+    #[test]
+    fn test_with_animation_frames() {
+        let mut acs = load("Bonzi.acs");
+        let mut durations = Vec::new();
+        acs.with_animation_frames("Explain", |i, frame| {
+            assert_eq!(i, durations.len());
+            durations.push(frame.duration_ms);
+        })
+        .unwrap();
+
+        let animation = acs.animation("Explain").unwrap();
+        let expected: Vec<u32> = animation.frames.iter().map(|f| f.duration_ms).collect();
+        assert_eq!(durations, expected);
+        assert!(acs.with_animation_frames("Missing", |_, _| {}).is_err());
+    }
+
+    #[test]
+    fn test_shared_reader_matches_fresh_readers() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let bytes = std::fs::read(path).unwrap();
+        let mut acs = Acs::new(bytes.clone()).unwrap();
+        let palette = &acs.character_info().palette;
+
+        // Walk the images backwards, reading a sound between each, so the shared
+        // reader never starts where the previous read left it
+        for (index, entry) in acs.lists().image_list.iter().enumerate().rev() {
+            acs.sound(index % acs.sound_count()).unwrap();
+            let raw = AcsReader::new(&bytes)
+                .read_image_info(entry.locator.offset)
+                .unwrap();
+            let fresh = acs.decode_image(&raw, palette).unwrap();
+            let shared = acs.image(index).unwrap();
+            assert_eq!(
+                (shared.width, shared.height),
+                (fresh.width, fresh.height),
+                "image {}",
+                index
+            );
+            assert!(shared.data == fresh.data, "image {} differs", index);
+        }
+
+        // Callbacks can read through `acs` between frames without disturbing the walk
+        let mut frames = 0;
+        acs.with_animation_frames("Explain", |_, frame| {
+            for image in &frame.images {
+                acs.image(image.image_index).unwrap();
             }
+            frames += 1;
+        })
+        .unwrap();
+        assert_eq!(frames, acs.frame_count("Explain").unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut acs = load("Bonzi.acs");
+        let animation = acs.animation("Greet").unwrap();
+        let json = serde_json::to_string(animation).unwrap();
+        let back: Animation = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.name, animation.name);
+        assert_eq!(back.frames.len(), animation.frames.len());
+        assert_eq!(serde_json::to_string(&back).unwrap(), json);
+
+        let info = acs.character_info();
+        let json = serde_json::to_string(info).unwrap();
+        assert!(json.contains(&format!("\"guid\":\"{:02x}", info.guid[0])));
+        let back: CharacterInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.guid, info.guid);
+        assert_eq!(back.palette, info.palette);
+    }
+
+    #[test]
+    fn test_transition_type_strings() {
+        for t in [
+            TransitionType::UseReturnAnimation,
+            TransitionType::UseExitBranch,
+            TransitionType::None,
+        ] {
+            assert_eq!(t.to_string().parse::<TransitionType>(), Ok(t));
+        }
+        assert_eq!("useexitbranch".parse(), Ok(TransitionType::UseExitBranch));
+        assert!("Sideways".parse::<TransitionType>().is_err());
+    }
+
+    #[test]
+    fn test_render_mouth_scale_background() {
+        let mut acs = load("Bonzi.acs");
+        acs.animation("Decline").unwrap();
+        let plain = acs.render_frame("Decline", 0).unwrap();
+
+        let wide = RenderOptions {
+            mouth: Some(OverlayType::MouthWide4),
+            ..RenderOptions::default()
         };
+        let speaking = acs.render_frame_with_options("Decline", 0, &wide).unwrap();
+        assert_ne!(speaking.data, plain.data);
 
-        let frame = frame.ok_or(AcsError::InvalidImageIndex(frame_index))?;
-        self.composite_frame(frame)
+        let options = RenderOptions {
+            background: Some([0, 0, 255, 255]),
+            scale: 2,
+            ..RenderOptions::default()
+        };
+        let flat = acs
+            .render_frame_with_options("Decline", 0, &options)
+            .unwrap();
+        assert_eq!(
+            (flat.width, flat.height),
+            (plain.width * 2, plain.height * 2)
+        );
+        assert!(flat.data.chunks_exact(4).all(|px| px[3] == 255));
     }
 
-    fn composite_frame(&self, frame: &Frame) -> Result<Image, AcsError> {
-        let width = self.character_info.width as u32;
-        let height = self.character_info.height as u32;
+    #[test]
+    fn test_debug_overlay() {
+        use crate::render::DEBUG_OVERLAY_COLOR;
 
-        let mut canvas = vec![0u8; (width * height * 4) as usize];
+        let mut acs = load("Bonzi.acs");
+        let frame = acs.animation("Explain").unwrap().frames[0].clone();
+        let placed = &frame.images[0];
+        let img = acs.image(placed.image_index).unwrap();
+
+        let options = RenderOptions {
+            debug_overlay: true,
+            ..RenderOptions::default()
+        };
+        let rendered = acs
+            .render_frame_with_options("Explain", 0, &options)
+            .unwrap();
+
+        let (left, top) = (placed.x as u32, placed.y as u32);
+        let (right, bottom) = (left + img.width - 1, top + img.height - 1);
+        for (x, y) in [(left, top), (right, top), (left, bottom), (right, bottom)] {
+            let i = ((y * rendered.width + x) * 4) as usize;
+            assert_eq!(
+                &rendered.data[i..i + 4],
+                &DEBUG_OVERLAY_COLOR,
+                "({}, {})",
+                x,
+                y
+            );
+        }
 
-        for frame_img in frame.images.iter().rev() {
-            let img = self.image(frame_img.image_index)?;
+        // Off by default
+        let plain = acs.render_frame("Explain", 0).unwrap();
+        assert_ne!(plain.data, rendered.data);
+    }
 
-            // Blit the image onto the canvas
-            for y in 0..img.height {
-                for x in 0..img.width {
-                    let dst_x = frame_img.x as i32 + x as i32;
-                    let dst_y = frame_img.y as i32 + y as i32;
+    #[test]
+    fn test_image_order() {
+        let acs = load("Bonzi.acs");
+        // Two full-size images that overlap with different colors
+        let (a, b) = (acs.image(1).unwrap(), acs.image(2).unwrap());
+
+        // Find a pixel where both images are opaque but differ, so the order is visible
+        let w = a.width.min(b.width);
+        let (x, y) = (0..a.height.min(b.height))
+            .flat_map(|y| (0..w).map(move |x| (x, y)))
+            .find(|&(x, y)| {
+                let pa = &a.data[((y * a.width + x) * 4) as usize..][..4];
+                let pb = &b.data[((y * b.width + x) * 4) as usize..][..4];
+                pa[3] > 0 && pb[3] > 0 && pa != pb
+            })
+            .expect("images overlap with different colors");
+
+        let frame = Frame {
+            images: vec![
+                FrameImage {
+                    image_index: 1,
+                    x: 0,
+                    y: 0,
+                },
+                FrameImage {
+                    image_index: 2,
+                    x: 0,
+                    y: 0,
+                },
+            ],
+            duration_ms: 100,
+            sound_index: None,
+            exit_branch: None,
+            branches: Vec::new(),
+            overlays: Vec::new(),
+        };
 
-                    if dst_x >= 0 && dst_x < width as i32 && dst_y >= 0 && dst_y < height as i32 {
-                        let src_idx = ((y * img.width + x) * 4) as usize;
-                        let dst_idx = ((dst_y as u32 * width + dst_x as u32) * 4) as usize;
+        let pixel = |order: ImageOrder| {
+            let options = RenderOptions {
+                image_order: order,
+                ..Default::default()
+            };
+            let image = acs.composite_frame(&frame, &options).unwrap();
+            let idx = ((y * image.width + x) * 4) as usize;
+            image.data[idx..idx + 4].to_vec()
+        };
 
-                        let alpha = img.data[src_idx + 3];
-                        if alpha > 0 {
-                            canvas[dst_idx] = img.data[src_idx];
-                            canvas[dst_idx + 1] = img.data[src_idx + 1];
-                            canvas[dst_idx + 2] = img.data[src_idx + 2];
-                            canvas[dst_idx + 3] = alpha;
-                        }
+        let top_of = |img: &Image| img.data[((y * img.width + x) * 4) as usize..][..4].to_vec();
+        assert_eq!(pixel(ImageOrder::Reversed), top_of(&a));
+        assert_eq!(pixel(ImageOrder::AsStored), top_of(&b));
+    }
+
+    #[test]
+    fn test_new_lazy() {
+        let path = format!("{}/../notes/files/Bonzi.acs", env!("CARGO_MANIFEST_DIR"));
+        let data = std::fs::read(path).unwrap();
+        let eager = Acs::new(data.clone()).unwrap();
+        let mut lazy = Acs::new_lazy(data).unwrap();
+
+        assert!(lazy.lists.get().is_none());
+        assert_eq!(lazy.character_info().name, eager.character_info().name);
+        assert!(lazy.lists.get().is_none());
+
+        assert_eq!(lazy.animation_names(), eager.animation_names());
+        assert_eq!(lazy.image_count(), eager.image_count());
+        assert!(lazy.load_warnings().is_empty());
+        assert!(lazy.animation("Explain").is_ok());
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        let frame = Frame {
+            images: Vec::new(),
+            duration_ms: 100,
+            sound_index: None,
+            exit_branch: None,
+            branches: Vec::new(),
+            overlays: vec![Overlay {
+                overlay_type: OverlayType::MouthWide1,
+                replace_enabled: false,
+                image_index: 0x5A5A,
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 1,
+            }],
+        };
+        let mut data = crate::writer::AcsBuilder::new(1, 1)
+            .image(1, 1, vec![0])
+            .animation(Animation {
+                name: "Talk".to_string(),
+                frames: vec![frame],
+                return_animation: None,
+                transition_type: TransitionType::None,
+            })
+            .build();
+        // Set the unknown byte after the overlay's image index
+        let unknown = data.windows(3).position(|w| w == [0x5A, 0x5A, 0]).unwrap() + 2;
+        data[unknown] = 7;
+
+        let mut lenient = Acs::new(data.clone()).unwrap();
+        assert!(lenient.animation("Talk").is_ok());
+
+        let strict = || Acs::with_parse_mode(data.clone(), ParseMode::Strict).unwrap();
+        let lazy = || Acs::new_lazy_with_parse_mode(data.clone(), ParseMode::Strict).unwrap();
+        for mut acs in [strict(), lazy()] {
+            assert!(matches!(
+                acs.animation("Talk"),
+                Err(AcsError::Reader(ReaderError::UnexpectedReserved {
+                    value: 7,
+                    ..
+                }))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_character_info_full() {
+        let acs = load("Bonzi.acs");
+        let full = acs.character_info_full();
+        let info = acs.character_info();
+
+        assert_eq!(full.name, info.name);
+        assert_eq!(full.guid, info.guid);
+        assert_eq!((full.width, full.height), (info.width, info.height));
+        assert_eq!(full.palette, info.palette);
+        assert_eq!(full.format_version, acs.format_version());
+        assert_eq!(full.anim_set_version, acs.anim_set_version());
+        assert_eq!(full.states.len(), acs.states().len());
+        assert_eq!(full.localized_info.len(), 1);
+        assert_eq!(
+            Some(full.localized_info[0].extra_data.as_str()),
+            acs.localized_extra(full.localized_info[0].lang_id)
+        );
+        assert!(!full.balloon_info.unwrap().font_name.is_empty());
+    }
+
+    #[test]
+    fn test_composite_clips_off_canvas_images() {
+        let acs = load("Bonzi.acs");
+        let image = acs.image(1).unwrap();
+        let shift = 50;
+
+        let frame = Frame {
+            images: vec![FrameImage {
+                image_index: 1,
+                x: -(shift as i16),
+                y: 0,
+            }],
+            duration_ms: 100,
+            sound_index: None,
+            exit_branch: None,
+            branches: Vec::new(),
+            overlays: Vec::new(),
+        };
+        let canvas = acs
+            .composite_frame(&frame, &RenderOptions::default())
+            .unwrap();
+
+        for y in 0..canvas.height {
+            for x in 0..canvas.width {
+                let dst = ((y * canvas.width + x) * 4) as usize;
+                let expected = if x + shift < image.width && y < image.height {
+                    let src = ((y * image.width + x + shift) * 4) as usize;
+                    if image.data[src + 3] > 0 {
+                        &image.data[src..src + 4]
+                    } else {
+                        &[0, 0, 0, 0][..]
                     }
+                } else {
+                    &[0, 0, 0, 0][..]
+                };
+                assert_eq!(&canvas.data[dst..dst + 4], expected, "pixel ({}, {})", x, y);
+            }
+        }
+
+        // Entirely off-canvas images are skipped
+        let mut offscreen = frame.clone();
+        offscreen.images[0].x = i16::MIN;
+        let blank = acs
+            .composite_frame(&offscreen, &RenderOptions::default())
+            .unwrap();
+        assert!(blank.data.iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_overlay_type_openness() {
+        assert!(OverlayType::MouthClosed.openness() < OverlayType::MouthMedium.openness());
+        assert!(OverlayType::MouthMedium.openness() < OverlayType::MouthWide1.openness());
+        assert!(OverlayType::MouthNarrow.openness() < OverlayType::MouthMedium.openness());
+        assert_eq!(OverlayType::Unknown(9).openness(), 0);
+        assert_eq!(OverlayType::Unknown(9).label(), "unknown");
+        assert_eq!(OverlayType::from(5).label(), "medium");
+    }
+
+    #[test]
+    fn test_can_remove_image() {
+        let mut acs = load("Bonzi.acs");
+        let rest_image = acs.animation("RestPose").unwrap().frames[0].images[0].image_index;
+        assert!(!acs.can_remove_image(rest_image));
+        assert!(!acs.can_remove_image(usize::MAX));
+    }
+
+    #[test]
+    fn test_animation_role() {
+        let acs = load("Bonzi.acs");
+        assert_eq!(
+            acs.animation_role("ReadReturn"),
+            AnimationRole::ReturnHelper
+        );
+        assert_eq!(acs.animation_role("Read"), AnimationRole::Playable);
+
+        let playable = acs.playable_animation_names();
+        assert!(playable.contains(&"Read"));
+        assert!(!playable.contains(&"ReadReturn"));
+    }
+
+    #[test]
+    fn test_unused_sounds() {
+        let mut acs = load("Bonzi.acs");
+        let unused = acs.unused_sounds();
+        assert!(unused.iter().all(|&i| i < acs.sound_count()));
+
+        let mut played = BTreeSet::new();
+        acs.each_animation(|_, anim| {
+            played.extend(anim.frames.iter().filter_map(|f| f.sound_index))
+        })
+        .unwrap();
+        assert!(!played.is_empty());
+        assert!(unused.iter().all(|i| !played.contains(i)));
+        assert_eq!(unused.len() + played.len(), acs.sound_count());
+    }
+
+    #[test]
+    fn test_with_sound_replaced() {
+        let mut acs = load("Bonzi.acs");
+        acs.rename_animation("Wave", "WaveHello").unwrap();
+        let format = crate::AudioFormat {
+            sample_rate: 11025,
+            channels: 1,
+            bits_per_sample: 16,
+        };
+        let wav = crate::wav::write_pcm16(format, &[1, -1, 2, -2]);
+
+        let replaced = Acs::new(acs.with_sound_replaced(1, &wav).unwrap()).unwrap();
+        assert_eq!(replaced.animation_names(), acs.animation_names());
+        assert_eq!(replaced.sound_count(), acs.sound_count());
+        assert_eq!(replaced.sound(1).unwrap().data, wav);
+        assert_eq!(replaced.sound(0).unwrap().data, acs.sound(0).unwrap().data);
+        assert_eq!(
+            replaced.render_frame("Explain", 0).unwrap().data,
+            acs.render_frame("Explain", 0).unwrap().data
+        );
+
+        assert!(matches!(
+            acs.with_sound_replaced(acs.sound_count(), &wav),
+            Err(AcsError::InvalidSoundIndex(_))
+        ));
+    }
+
+    #[test]
+    fn test_import_animation() {
+        let mut clippit = load("clippit.acs");
+        let palette = vec![[255, 0, 255], [0, 0, 0], [255, 255, 255], [255, 255, 0]];
+        let data = crate::writer::AcsBuilder::new(8, 8)
+            .palette(palette)
+            .transparent_color(0)
+            .image(1, 1, vec![1])
+            .animation(Animation {
+                name: "Blink".to_string(),
+                transition_type: TransitionType::None,
+                return_animation: None,
+                frames: vec![],
+            })
+            .build();
+        let mut acs = Acs::new(data).unwrap();
+
+        acs.import_animation(&mut clippit, "getartsy").unwrap();
+        assert!(matches!(
+            acs.import_animation(&mut clippit, "GetArtsy"),
+            Err(AcsError::DuplicateAnimation(_))
+        ));
+
+        let source = clippit.animation("GetArtsy").unwrap().clone();
+        let reparsed = Acs::new(acs.as_bytes().to_vec()).unwrap();
+        for acs in [&mut acs, &mut { reparsed }] {
+            assert_eq!(acs.animation_names(), ["Blink", "GetArtsy"]);
+            let imported = acs.animation("GetArtsy").unwrap().clone();
+            assert_eq!(imported.frames.len(), source.frames.len());
+
+            for (frame, original) in imported.frames.iter().zip(&source.frames) {
+                assert_eq!(frame.duration_ms, original.duration_ms);
+                assert!(
+                    frame
+                        .image_indices()
+                        .all(|i| i >= 1 && i < acs.image_count())
+                );
+                if let (Some(new), Some(old)) = (frame.sound_index, original.sound_index) {
+                    assert_eq!(
+                        acs.sound(new).unwrap().data,
+                        clippit.sound(old).unwrap().data
+                    );
+                }
+            }
+            assert!(acs.sound_count() > 0);
+
+            // Opaque pixels come from the target palette; transparency carries over
+            let image = acs.image(imported.frames[0].images[0].image_index).unwrap();
+            let original = clippit
+                .image(source.frames[0].images[0].image_index)
+                .unwrap();
+            assert_eq!(
+                (image.width, image.height),
+                (original.width, original.height)
+            );
+            assert!(original.data.chunks_exact(4).any(|p| p[3] == 0));
+            for (new, old) in image
+                .data
+                .chunks_exact(4)
+                .zip(original.data.chunks_exact(4))
+            {
+                assert_eq!(new[3], old[3]);
+                if new[3] == 255 {
+                    assert!(matches!(
+                        new[..3],
+                        [0, 0, 0] | [255, 255, 255] | [255, 255, 0]
+                    ));
                 }
             }
         }
+    }
 
-        Ok(Image {
-            width,
-            height,
-            data: canvas,
+    #[test]
+    fn test_estimated_decoded_bytes() {
+        let acs = load("Bonzi.acs");
+        let on_disk: usize = acs
+            .lists()
+            .image_list
+            .iter()
+            .map(|entry| entry.locator.size as usize)
+            .sum();
+        let estimate = acs.estimated_decoded_bytes();
+        assert!(estimate > 0);
+        assert!(estimate > on_disk);
+
+        let first = acs.image(0).unwrap();
+        assert!(estimate >= first.data.len());
+    }
+
+    #[test]
+    fn test_unused_images() {
+        let mut acs = load("Bonzi.acs");
+        let unused = acs.unused_images();
+
+        let rest = &acs.animation("RestPose").unwrap().frames[0];
+        let rest_images: Vec<usize> = rest.image_indices().collect();
+        assert!(!rest_images.is_empty());
+        assert!(rest_images.iter().all(|i| !unused.contains(i)));
+        assert!(unused.iter().all(|&i| acs.can_remove_image(i)));
+    }
+
+    #[test]
+    fn test_rename_animation() {
+        let mut acs = load("Bonzi.acs");
+        acs.rename_animation("ReadReturn", "ReadingDone").unwrap();
+
+        let names = acs.animation_names();
+        assert!(names.contains(&"ReadingDone"));
+        assert!(!names.iter().any(|n| n.eq_ignore_ascii_case("ReadReturn")));
+        assert_eq!(
+            acs.animation("Reading")
+                .unwrap()
+                .return_animation
+                .as_deref(),
+            Some("ReadingDone")
+        );
+
+        assert!(matches!(
+            acs.rename_animation("ReadingDone", "Explain"),
+            Err(AcsError::DuplicateAnimation(_))
+        ));
+        assert!(matches!(
+            acs.rename_animation("ReadReturn", "Anything"),
+            Err(AcsError::AnimationNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_audio_list_is_recoverable() {
+        let path = format!("{}/../notes/files/Bonzi.acs", env!("CARGO_MANIFEST_DIR"));
+        let mut data = std::fs::read(path).unwrap();
+        // Point the audio locator past the end of the file
+        let bad_offset = data.len() as u32 + 1000;
+        data[28..32].copy_from_slice(&bad_offset.to_le_bytes());
+
+        let mut acs = Acs::new(data).unwrap();
+        assert_eq!(acs.load_warnings().len(), 1);
+        assert_eq!(acs.sound_count(), 0);
+        assert!(acs.image_count() > 0);
+        assert!(acs.animation("Explain").is_ok());
+        assert!(acs.render_frame("Explain", 0).is_ok());
+    }
+
+    #[test]
+    fn test_each_animation() {
+        let mut acs = load("clippit.acs");
+        let names: Vec<String> = acs
+            .animation_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let expected: usize = names
+            .iter()
+            .map(|name| acs.animation(name).unwrap().frames.len())
+            .sum();
+
+        let mut total_frames = 0;
+        let mut visited = 0;
+        acs.each_animation(|_, anim| {
+            total_frames += anim.frames.len();
+            visited += 1;
         })
+        .unwrap();
+        assert_eq!(visited, names.len());
+        assert_eq!(total_frames, expected);
+
+        let mut total_frames = 0;
+        let errors = acs.try_each_animation(|_, anim| total_frames += anim.frames.len());
+        assert!(errors.is_empty());
+        assert_eq!(total_frames, expected);
+    }
+
+    #[test]
+    fn test_image_bmp() {
+        let acs = load("Bonzi.acs");
+        let image = acs.image(0).unwrap();
+        let bmp = acs.image_bmp(0).unwrap();
+
+        assert_eq!(&bmp[..2], b"BM");
+        let file_size = u32::from_le_bytes(bmp[2..6].try_into().unwrap());
+        assert_eq!(file_size as usize, bmp.len());
+        let width = i32::from_le_bytes(bmp[18..22].try_into().unwrap());
+        let height = i32::from_le_bytes(bmp[22..26].try_into().unwrap());
+        assert_eq!(width as u32, image.width);
+        assert_eq!(height as u32, image.height);
+        assert_eq!(u16::from_le_bytes([bmp[28], bmp[29]]), 8);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_render_frame_png() {
+        let mut acs = load("Bonzi.acs");
+        let bytes = acs.render_frame_png("Explain", 0).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+
+        let decoder = png::Decoder::new(&bytes[..]);
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!(info.width, acs.character_info().width as u32);
+        assert_eq!(info.height, acs.character_info().height as u32);
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+    }
+
+    #[cfg(feature = "image-crate")]
+    #[test]
+    fn test_image_crate_round_trip() {
+        let acs = load("Bonzi.acs");
+        let frame = acs.render_frame("Explain", 0).unwrap();
+        let buffer = frame.to_image_buffer().unwrap();
+        assert_eq!(buffer.dimensions(), (frame.width, frame.height));
+
+        let mut encoded = std::io::Cursor::new(Vec::new());
+        buffer
+            .write_to(&mut encoded, image::ImageFormat::Png)
+            .unwrap();
+        let decoded = image::load_from_memory(encoded.get_ref())
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(decoded.into_raw(), frame.data);
+
+        let mut truncated = frame.clone();
+        truncated.data.pop();
+        assert!(matches!(
+            image::RgbaImage::try_from(truncated),
+            Err(AcsError::InvalidImageData { .. })
+        ));
     }
 }
@@ -0,0 +1,204 @@
+//! Sprite-sheet packing for game engine integrations.
+
+use crate::acs::{Acs, AcsError, Image};
+
+/// An animation packed into a single sprite sheet.
+///
+/// Dependency-free, so any engine can build its own texture atlas from it.
+#[derive(Debug, Clone)]
+pub struct AtlasLayout {
+    /// The packed RGBA sprite sheet.
+    pub image: Image,
+    /// Each frame's rectangle in the sheet as `(x, y, width, height)`.
+    pub rects: Vec<(u32, u32, u32, u32)>,
+    /// Each frame's duration in milliseconds.
+    pub durations_ms: Vec<u32>,
+}
+
+impl Acs {
+    /// Render every frame of an animation into a sprite sheet.
+    ///
+    /// Frames are laid out left-to-right, top-to-bottom in a near-square grid of
+    /// character-sized cells. Fails if the sheet's dimensions or buffer size don't fit
+    /// in memory.
+    pub fn atlas_layout(&mut self, animation: &str) -> Result<AtlasLayout, AcsError> {
+        let frame_count = self.animation(animation)?.frames.len();
+        let cell_w = self.character_info().width as u32;
+        let cell_h = self.character_info().height as u32;
+
+        let cols = (frame_count as f64).sqrt().ceil().max(1.0) as u32;
+        let rows = (frame_count as u32).div_ceil(cols).max(1);
+        let (sheet_w, sheet_h, len) = sheet_size(cols, rows, cell_w, cell_h)?;
+
+        let mut data = vec![0u8; len];
+        let mut rects = Vec::with_capacity(frame_count);
+        let mut durations_ms = Vec::with_capacity(frame_count);
+
+        for i in 0..frame_count {
+            let frame = self.render_frame(animation, i)?;
+            let x = (i as u32 % cols) * cell_w;
+            let y = (i as u32 / cols) * cell_h;
+
+            let row_bytes = cell_w as usize * 4;
+            for row in 0..cell_h as usize {
+                let src = row * row_bytes;
+                let dst = ((y as usize + row) * sheet_w as usize + x as usize) * 4;
+                data[dst..dst + row_bytes].copy_from_slice(&frame.data[src..src + row_bytes]);
+            }
+
+            rects.push((x, y, cell_w, cell_h));
+            durations_ms.push(self.animation(animation)?.frames[i].duration_ms);
+        }
+
+        Ok(AtlasLayout {
+            image: Image {
+                width: sheet_w,
+                height: sheet_h,
+                data,
+            },
+            rects,
+            durations_ms,
+        })
+    }
+
+    /// Tile frame 0 of every playable animation into one image, for a character browser.
+    ///
+    /// Cells are the character size divided by `cell_scale` (box-filtered), laid out
+    /// `cols` to a row in `playable_animation_names` order. Animations without frames
+    /// leave their cell empty. `cols` and `cell_scale` of 0 are treated as 1. Fails if
+    /// the sheet's dimensions or buffer size don't fit in memory.
+    pub fn contact_sheet(&mut self, cols: u32, cell_scale: u32) -> Result<Image, AcsError> {
+        let cols = cols.max(1);
+        let cell_scale = cell_scale.max(1);
+        let cell_w = (self.character_info().width as u32 / cell_scale).max(1);
+        let cell_h = (self.character_info().height as u32 / cell_scale).max(1);
+
+        let names: Vec<String> = self
+            .playable_animation_names()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let rows = (names.len() as u32).div_ceil(cols);
+        let (width, height, len) = sheet_size(cols, rows, cell_w, cell_h)?;
+        let mut sheet = Image {
+            width,
+            height,
+            data: vec![0u8; len],
+        };
+
+        for (i, name) in names.iter().enumerate() {
+            if self.animation(name)?.frames.is_empty() {
+                continue;
+            }
+            let thumb = self.render_frame(name, 0)?.downscale(cell_w, cell_h);
+            let x = (i as u32 % cols) * cell_w;
+            let y = (i as u32 / cols) * cell_h;
+            sheet.blit(&thumb, x as i64, y as i64);
+        }
+
+        Ok(sheet)
+    }
+}
+
+/// Dimensions and RGBA buffer length of a sheet of `cols` x `rows` cells.
+fn sheet_size(
+    cols: u32,
+    rows: u32,
+    cell_w: u32,
+    cell_h: u32,
+) -> Result<(u32, u32, usize), AcsError> {
+    let overflow = AcsError::SheetOverflow {
+        cols,
+        rows,
+        cell_width: cell_w,
+        cell_height: cell_h,
+    };
+    let (Some(width), Some(height)) = (cols.checked_mul(cell_w), rows.checked_mul(cell_h)) else {
+        return Err(overflow);
+    };
+    let Some(len) = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|n| n.checked_mul(4))
+    else {
+        return Err(overflow);
+    };
+    Ok((width, height, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_sheet() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let mut acs = Acs::new(std::fs::read(path).unwrap()).unwrap();
+        let playable = acs.playable_animation_names().len() as u32;
+        let (width, height) = (
+            acs.character_info().width as u32,
+            acs.character_info().height as u32,
+        );
+
+        let sheet = acs.contact_sheet(8, 4).unwrap();
+        assert_eq!(sheet.width, 8 * (width / 4));
+        assert_eq!(sheet.height, playable.div_ceil(8) * (height / 4));
+        assert_eq!(sheet.data.len(), (sheet.width * sheet.height * 4) as usize);
+        assert!(sheet.data.chunks_exact(4).any(|px| px[3] > 0));
+
+        assert!(matches!(
+            acs.contact_sheet(u32::MAX, 1),
+            Err(AcsError::SheetOverflow { cols: u32::MAX, .. })
+        ));
+    }
+
+    #[test]
+    fn test_sheet_size_overflow() {
+        assert_eq!(sheet_size(2, 3, 10, 20).unwrap(), (20, 60, 20 * 60 * 4));
+        assert!(matches!(
+            sheet_size(0x1_0000, 1, 0x1_0000, 1),
+            Err(AcsError::SheetOverflow { .. })
+        ));
+        assert!(matches!(
+            sheet_size(u32::MAX, u32::MAX, 1, 1),
+            Err(AcsError::SheetOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_atlas_layout() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let mut acs = Acs::new(std::fs::read(path).unwrap()).unwrap();
+        let atlas = acs.atlas_layout("Explain").unwrap();
+        let frame_count = acs.animation("Explain").unwrap().frames.len();
+
+        assert_eq!(atlas.rects.len(), frame_count);
+        assert_eq!(atlas.durations_ms.len(), frame_count);
+        assert_eq!(
+            atlas.image.data.len(),
+            (atlas.image.width * atlas.image.height * 4) as usize
+        );
+
+        // No two rects overlap
+        for (i, a) in atlas.rects.iter().enumerate() {
+            for b in &atlas.rects[i + 1..] {
+                let disjoint =
+                    a.0 + a.2 <= b.0 || b.0 + b.2 <= a.0 || a.1 + a.3 <= b.1 || b.1 + b.3 <= a.1;
+                assert!(disjoint, "{:?} overlaps {:?}", a, b);
+            }
+        }
+
+        // The rects span the whole sheet
+        let max_x = atlas.rects.iter().map(|r| r.0 + r.2).max().unwrap();
+        let max_y = atlas.rects.iter().map(|r| r.1 + r.3).max().unwrap();
+        assert_eq!((max_x, max_y), (atlas.image.width, atlas.image.height));
+
+        // Each cell holds the rendered frame
+        let (x, y, w, _) = atlas.rects[1];
+        let frame = acs.render_frame("Explain", 1).unwrap();
+        let dst = ((y * atlas.image.width + x) * 4) as usize;
+        assert_eq!(
+            &atlas.image.data[dst..dst + (w * 4) as usize],
+            &frame.data[..(w * 4) as usize]
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Sound format inspection and PCM mixing.
+
+use std::collections::BTreeMap;
+
+use crate::acs::{Acs, AcsError, Sound};
+use crate::wav::{self, WavError};
+
+/// Sample format of a WAV sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AudioFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+}
+
+/// Distinct sound formats in a character file, with how many sounds use each.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AudioSummary {
+    pub formats: BTreeMap<AudioFormat, usize>,
+}
+
+impl AudioSummary {
+    /// Whether every sound shares a single format.
+    pub fn is_uniform(&self) -> bool {
+        self.formats.len() <= 1
+    }
+}
+
+/// Mix two interleaved 16-bit PCM buffers by saturating addition.
+///
+/// The output is as long as the longer input; the shorter one is treated as silence
+/// once it runs out.
+pub fn mix_pcm16(a: &[i16], b: &[i16]) -> Vec<i16> {
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.saturating_add(y)
+        })
+        .collect()
+}
+
+/// Average each frame of interleaved 16-bit PCM down to a single channel.
+pub(crate) fn downmix_pcm16(samples: &[i16], channels: u16) -> Vec<i16> {
+    let channels = channels.max(1) as usize;
+    samples
+        .chunks_exact(channels)
+        .map(|frame| (frame.iter().map(|&s| s as i32).sum::<i32>() / channels as i32) as i16)
+        .collect()
+}
+
+/// Mix two 16-bit PCM WAV files into one, e.g. a character sound over TTS output.
+///
+/// Both files must have the same sample rate and channel count; resample first if not.
+pub fn mix_wav(a: &[u8], b: &[u8]) -> Result<Vec<u8>, WavError> {
+    let (format_a, samples_a) = wav::extract_pcm16(a)?;
+    let (format_b, samples_b) = wav::extract_pcm16(b)?;
+    if format_a != format_b {
+        return Err(WavError::FormatMismatch(format_a, format_b));
+    }
+    Ok(wav::write_pcm16(
+        format_a,
+        &mix_pcm16(&samples_a, &samples_b),
+    ))
+}
+
+impl Sound {
+    /// Parse the sample format from the WAV header.
+    pub fn format(&self) -> Result<AudioFormat, AcsError> {
+        Ok(wav::parse_header(&self.data)?.format)
+    }
+
+    /// Playback length in milliseconds, from the WAV byte rate and data chunk length.
+    pub fn duration_ms(&self) -> Result<u32, AcsError> {
+        let header = wav::parse_header(&self.data)?;
+        let data = header
+            .data
+            .ok_or(WavError::Malformed("missing data chunk"))?;
+        if header.byte_rate == 0 {
+            return Err(WavError::Malformed("zero byte rate").into());
+        }
+        Ok((data.len() as u64 * 1000 / header.byte_rate as u64) as u32)
+    }
+
+    /// Decode a 16-bit PCM sound into its format and interleaved samples.
+    pub fn pcm16(&self) -> Result<(AudioFormat, Vec<i16>), AcsError> {
+        Ok(wav::extract_pcm16(&self.data)?)
+    }
+
+    /// The sample format and raw bytes of the `data` chunk.
+    #[cfg(feature = "audio-transcode")]
+    pub(crate) fn sample_data(&self) -> Result<(AudioFormat, &[u8]), AcsError> {
+        Ok(wav::pcm_data(&self.data)?)
+    }
+}
+
+impl Acs {
+    /// Get a sound's playback length in milliseconds.
+    pub fn sound_duration_ms(&self, index: usize) -> Result<u32, AcsError> {
+        self.sound(index)?.duration_ms()
+    }
+
+    /// Collect the distinct sample formats used by the file's sounds.
+    ///
+    /// Useful for spotting files that mix sample rates before setting up an audio pipeline.
+    pub fn audio_format_summary(&self) -> Result<AudioSummary, AcsError> {
+        let mut summary = AudioSummary::default();
+        for index in 0..self.sound_count() {
+            let format = self.sound(index)?.format()?;
+            *summary.formats.entry(format).or_default() += 1;
+        }
+        Ok(summary)
+    }
+
+    /// Get a sound as mono 16-bit WAV bytes, averaging the channels of each sample frame.
+    ///
+    /// Many Agent sounds are stereo with identical channels, so this halves their size.
+    /// Mono sounds are returned unchanged.
+    pub fn sound_mono(&self, index: usize) -> Result<Vec<u8>, AcsError> {
+        let sound = self.sound(index)?;
+        if sound.format()?.channels == 1 {
+            return Ok(sound.data);
+        }
+
+        let (format, samples) = sound.pcm16()?;
+        let mono = downmix_pcm16(&samples, format.channels);
+        Ok(wav::write_pcm16(
+            AudioFormat {
+                channels: 1,
+                ..format
+            },
+            &mono,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_format_summary() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let acs = Acs::new(std::fs::read(path).unwrap()).unwrap();
+
+        let summary = acs.audio_format_summary().unwrap();
+        assert!(!summary.formats.is_empty());
+        assert_eq!(summary.formats.values().sum::<usize>(), acs.sound_count());
+    }
+
+    #[test]
+    fn test_sound_duration() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let acs = Acs::new(std::fs::read(path).unwrap()).unwrap();
+
+        for index in 0..acs.sound_count() {
+            let ms = acs.sound_duration_ms(index).unwrap();
+            assert!(ms > 0 && ms < 60_000, "sound {} lasts {}ms", index, ms);
+        }
+        assert!(matches!(
+            acs.sound_duration_ms(acs.sound_count()),
+            Err(AcsError::InvalidSoundIndex(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_non_wav() {
+        let sound = Sound {
+            data: b"not a wave file".to_vec(),
+        };
+        assert!(matches!(sound.format(), Err(AcsError::InvalidWav(_))));
+        assert!(matches!(sound.duration_ms(), Err(AcsError::InvalidWav(_))));
+    }
+
+    #[test]
+    fn test_sound_mono() {
+        let stereo = AudioFormat {
+            sample_rate: 11025,
+            channels: 2,
+            bits_per_sample: 16,
+        };
+        let mono = AudioFormat {
+            channels: 1,
+            ..stereo
+        };
+        let stereo_wav = wav::write_pcm16(stereo, &[100, 200, -100, -300, i16::MAX, i16::MAX]);
+        let mono_wav = wav::write_pcm16(mono, &[1, 2, 3]);
+        let data = crate::writer::AcsBuilder::new(1, 1)
+            .sound(stereo_wav.clone())
+            .sound(mono_wav.clone())
+            .build();
+        let acs = Acs::new(data).unwrap();
+
+        let downmixed = acs.sound_mono(0).unwrap();
+        assert_eq!(downmixed.len() - 44, (stereo_wav.len() - 44) / 2);
+        assert_eq!(
+            wav::extract_pcm16(&downmixed).unwrap(),
+            (mono, vec![150, -200, i16::MAX])
+        );
+        assert_eq!(acs.sound_mono(1).unwrap(), mono_wav);
+    }
+
+    #[test]
+    fn test_mix_pcm16_saturates() {
+        let mixed = mix_pcm16(&[30_000, -30_000, 100], &[10_000, -10_000]);
+        assert_eq!(mixed, [i16::MAX, i16::MIN, 100]);
+    }
+
+    #[test]
+    fn test_mix_wav() {
+        let mono = AudioFormat {
+            sample_rate: 22050,
+            channels: 1,
+            bits_per_sample: 16,
+        };
+        let a = wav::write_pcm16(mono, &[1, 2, 3]);
+        let b = wav::write_pcm16(mono, &[10, 20]);
+        let mixed = mix_wav(&a, &b).unwrap();
+        assert_eq!(wav::extract_pcm16(&mixed).unwrap(), (mono, vec![11, 22, 3]));
+
+        let stereo = AudioFormat {
+            channels: 2,
+            ..mono
+        };
+        let c = wav::write_pcm16(stereo, &[1, 2]);
+        assert_eq!(mix_wav(&a, &c), Err(WavError::FormatMismatch(mono, stereo)));
+    }
+}
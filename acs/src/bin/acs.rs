@@ -0,0 +1,204 @@
+//! Command-line tools for working with ACS character files.
+//!
+//! ```text
+//! acs export --acs <file.acs> --animation <name> --out-dir <dir> [--scale <n>]
+//! acs export-palette --acs <file.acs> --out <file.png> [--swatch <px>]
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use acs::{Acs, Image};
+
+const USAGE: &str = "Usage:
+  acs export --acs <file.acs> --animation <name> --out-dir <dir> [--scale <n>]
+  acs export-palette --acs <file.acs> --out <file.png> [--swatch <px>]";
+
+/// Side length of a palette swatch when `--swatch` isn't given.
+const DEFAULT_SWATCH: u32 = 16;
+
+/// Parsed `--flag value` pairs following the subcommand.
+struct Args {
+    values: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        while let Some(arg) = args.next() {
+            let flag = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unexpected argument '{}'", arg))?;
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for --{}", flag))?;
+            values.insert(flag.to_string(), value);
+        }
+        Ok(Self { values })
+    }
+
+    fn required(&self, flag: &str) -> Result<&str, String> {
+        self.values
+            .get(flag)
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing required --{}", flag))
+    }
+
+    /// A positive integer flag, or `default` when absent.
+    fn positive(&self, flag: &str, default: u32) -> Result<u32, String> {
+        match self.values.get(flag) {
+            None => Ok(default),
+            Some(s) => match s.parse() {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err(format!("invalid --{} '{}'", flag, s)),
+            },
+        }
+    }
+}
+
+/// Escape a string for embedding in a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Write every frame of an animation as `frame_NNN.png` plus a `frames.json` manifest.
+fn export(args: &Args) -> Result<(), String> {
+    let path = args.required("acs")?;
+    let animation = args.required("animation")?;
+    let out_dir = Path::new(args.required("out-dir")?);
+    let scale = args.positive("scale", 1)?;
+
+    let mut acs = Acs::from_path(path).map_err(|e| format!("{}: {}", path, e))?;
+    let frames: Vec<(u32, Option<usize>)> = acs
+        .animation(animation)
+        .map_err(|e| e.to_string())?
+        .frames
+        .iter()
+        .map(|f| (f.duration_ms, f.sound_index))
+        .collect();
+
+    fs::create_dir_all(out_dir).map_err(|e| format!("{}: {}", out_dir.display(), e))?;
+
+    let mut entries = Vec::with_capacity(frames.len());
+    for (i, (duration_ms, sound_index)) in frames.into_iter().enumerate() {
+        let png = if scale == 1 {
+            acs.render_frame_png(animation, i)
+        } else {
+            acs.render_frame(animation, i)
+                .and_then(|image| image.scale_nearest(scale)?.to_png())
+        }
+        .map_err(|e| format!("frame {}: {}", i, e))?;
+
+        let file = format!("frame_{:03}.png", i);
+        let file_path = out_dir.join(&file);
+        fs::write(&file_path, png).map_err(|e| format!("{}: {}", file_path.display(), e))?;
+
+        let sound = sound_index.map_or("null".to_string(), |s| s.to_string());
+        entries.push(format!(
+            "    {{ \"file\": {}, \"durationMs\": {}, \"soundIndex\": {} }}",
+            json_string(&file),
+            duration_ms,
+            sound
+        ));
+    }
+
+    let manifest = format!(
+        "{{\n  \"animation\": {},\n  \"scale\": {},\n  \"frames\": [\n{}\n  ]\n}}\n",
+        json_string(animation),
+        scale,
+        entries.join(",\n")
+    );
+    let manifest_path = out_dir.join("frames.json");
+    fs::write(&manifest_path, manifest)
+        .map_err(|e| format!("{}: {}", manifest_path.display(), e))?;
+
+    println!("Wrote {} frames to {}", entries.len(), out_dir.display());
+    Ok(())
+}
+
+/// Lay the palette out as a 16x16 grid of `swatch`-pixel squares, in index order.
+///
+/// The transparent index is drawn with a checkerboard so it stands out from its
+/// neighbours; unused slots past the end of the palette are left fully transparent.
+fn palette_swatches(palette: &[[u8; 4]], transparent: usize, swatch: u32) -> Image {
+    let size = swatch * 16;
+    let mut data = vec![0u8; size as usize * size as usize * 4];
+
+    for (index, color) in palette.iter().enumerate().take(256) {
+        let (col, row) = (index as u32 % 16, index as u32 / 16);
+        for y in 0..swatch {
+            for x in 0..swatch {
+                let checker =
+                    index == transparent && ((x * 4 / swatch) + (y * 4 / swatch)) % 2 == 1;
+                let px = if checker {
+                    [255, 255, 255, 255]
+                } else {
+                    *color
+                };
+                let offset = (((row * swatch + y) * size + col * swatch + x) * 4) as usize;
+                data[offset..offset + 4].copy_from_slice(&px);
+            }
+        }
+    }
+
+    Image {
+        width: size,
+        height: size,
+        data,
+    }
+}
+
+/// Write the character palette as a PNG grid of color swatches.
+fn export_palette(args: &Args) -> Result<(), String> {
+    let path = args.required("acs")?;
+    let out = Path::new(args.required("out")?);
+    let swatch = args.positive("swatch", DEFAULT_SWATCH)?;
+
+    let acs = Acs::from_path(path).map_err(|e| format!("{}: {}", path, e))?;
+    let info = acs.character_info();
+    let png = palette_swatches(&info.palette, info.transparent_color as usize, swatch)
+        .to_png()
+        .map_err(|e| e.to_string())?;
+    fs::write(out, png).map_err(|e| format!("{}: {}", out.display(), e))?;
+
+    println!(
+        "Wrote {} palette entries to {} (transparent index {})",
+        info.palette.len(),
+        out.display(),
+        info.transparent_color
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let mut argv = std::env::args().skip(1);
+    let command = argv.next();
+
+    let result = Args::parse(argv).and_then(|args| match command.as_deref() {
+        Some("export") => export(&args),
+        Some("export-palette") => export_palette(&args),
+        Some(other) => Err(format!("unknown command '{}'", other)),
+        None => Err("no command given".to_string()),
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -28,8 +28,24 @@ impl fmt::Display for DecompressionError {
 
 impl std::error::Error for DecompressionError {}
 
+/// How many output bytes `decompress_with_progress` produces between callbacks.
+pub const PROGRESS_INTERVAL: usize = 64 * 1024;
+
 pub fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, DecompressionError> {
+    decompress_with_progress(bytes, |_| {})
+}
+
+/// Decompress like [`decompress`], calling `progress` with the number of bytes output
+/// so far roughly every [`PROGRESS_INTERVAL`] bytes, and with the final total on
+/// completion (unless that was just reported).
+///
+/// Meant for showing a progress bar while unpacking very large images.
+pub fn decompress_with_progress(
+    bytes: Vec<u8>,
+    mut progress: impl FnMut(usize),
+) -> Result<Vec<u8>, DecompressionError> {
     let mut ret = Vec::new();
+    let mut reported = 0;
 
     let mut bits = Bits::new(bytes);
 
@@ -123,6 +139,14 @@ pub fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, DecompressionError> {
                 ret.push(b);
             }
         }
+
+        if ret.len() - reported >= PROGRESS_INTERVAL {
+            reported = ret.len();
+            progress(reported);
+        }
+    }
+    if ret.len() != reported {
+        progress(ret.len());
     }
     Ok(ret)
 }
@@ -149,4 +173,41 @@ mod tests {
         let result = decompress(compressed).expect("decompression failed");
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_decompress_with_progress() {
+        // Literal 0x2A, then a back-reference at offset 1 of maximum length (4096 bytes),
+        // repeated until the output spans a few progress intervals.
+        let mut bits = Vec::new();
+        bits.push(false);
+        bits.extend((0..8).map(|i| 0x2A >> i & 1 == 1));
+        for _ in 0..(PROGRESS_INTERVAL * 3 / 4096) {
+            bits.extend([true, false]);
+            bits.extend([false; 6]);
+            bits.extend([true; 11]);
+            bits.push(false);
+            bits.extend([true; 11]);
+        }
+        // End-of-stream marker
+        bits.extend([true; 4]);
+        bits.extend([true; 20]);
+
+        let mut compressed = vec![0];
+        compressed.extend(bits.chunks(8).map(|byte| {
+            byte.iter()
+                .enumerate()
+                .fold(0u8, |acc, (i, &bit)| acc | (bit as u8) << i)
+        }));
+
+        let mut reports = Vec::new();
+        let result = decompress_with_progress(compressed.clone(), |n| reports.push(n)).unwrap();
+        let expected = decompress(compressed).unwrap();
+
+        assert_eq!(result, expected);
+        assert!(result.len() > PROGRESS_INTERVAL * 2);
+        assert!(result.iter().all(|&b| b == 0x2A));
+        assert!(reports.len() >= 3);
+        assert!(reports.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(reports.last(), Some(&result.len()));
+    }
 }
@@ -0,0 +1,168 @@
+//! Structural comparison of two character files.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::acs::{Acs, Animation};
+
+/// Differences between two characters, as reported by [`diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CharacterDiff {
+    /// Animations only present in the second character.
+    pub added_animations: Vec<String>,
+    /// Animations only present in the first character.
+    pub removed_animations: Vec<String>,
+    /// Animations whose frames are identical but whose name changed, as `(old, new)`.
+    pub renamed_animations: Vec<(String, String)>,
+    /// Character dimensions as `((width, height), (width, height))` if they differ.
+    pub dimensions: Option<((u16, u16), (u16, u16))>,
+    /// Palette indices whose colors differ (including entries present in only one palette).
+    pub palette_differences: Vec<usize>,
+    /// Image counts as `(first, second)` if they differ.
+    pub image_counts: Option<(usize, usize)>,
+}
+
+impl CharacterDiff {
+    /// Whether the two characters are structurally identical.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for CharacterDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+
+        if let Some(((w1, h1), (w2, h2))) = self.dimensions {
+            writeln!(f, "dimensions: {}x{} -> {}x{}", w1, h1, w2, h2)?;
+        }
+        if let Some((a, b)) = self.image_counts {
+            writeln!(f, "image count: {} -> {}", a, b)?;
+        }
+        if !self.palette_differences.is_empty() {
+            writeln!(
+                f,
+                "palette: {} entries differ",
+                self.palette_differences.len()
+            )?;
+        }
+        for name in &self.added_animations {
+            writeln!(f, "+ {}", name)?;
+        }
+        for name in &self.removed_animations {
+            writeln!(f, "- {}", name)?;
+        }
+        for (old, new) in &self.renamed_animations {
+            writeln!(f, "~ {} -> {}", old, new)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare two characters structurally.
+///
+/// Animation names are compared case-insensitively. A removed animation whose frames
+/// exactly match an added one is reported as a rename instead.
+pub fn diff(a: &Acs, b: &mut Acs) -> CharacterDiff {
+    let a_info = a.character_info();
+    let b_info = b.character_info();
+
+    let a_dims = (a_info.width, a_info.height);
+    let b_dims = (b_info.width, b_info.height);
+    let dimensions = (a_dims != b_dims).then_some((a_dims, b_dims));
+
+    let palette_len = a_info.palette.len().max(b_info.palette.len());
+    let palette_differences = (0..palette_len)
+        .filter(|&i| a_info.palette.get(i) != b_info.palette.get(i))
+        .collect();
+
+    let image_counts =
+        (a.image_count() != b.image_count()).then_some((a.image_count(), b.image_count()));
+
+    let a_names: Vec<String> = a.animation_names().iter().map(|s| s.to_string()).collect();
+    let b_names: Vec<String> = b.animation_names().iter().map(|s| s.to_string()).collect();
+    let a_lower: HashSet<String> = a_names.iter().map(|s| s.to_lowercase()).collect();
+    let b_lower: HashSet<String> = b_names.iter().map(|s| s.to_lowercase()).collect();
+
+    let mut removed_animations: Vec<String> = a_names
+        .iter()
+        .filter(|n| !b_lower.contains(&n.to_lowercase()))
+        .cloned()
+        .collect();
+    let mut added_animations: Vec<String> = b_names
+        .iter()
+        .filter(|n| !a_lower.contains(&n.to_lowercase()))
+        .cloned()
+        .collect();
+
+    let mut renamed_animations = Vec::new();
+    removed_animations.retain(|old| {
+        let Ok(old_anim) = a.animation_ref(old) else {
+            return true;
+        };
+        let matched = added_animations.iter().position(|new| {
+            b.animation(new)
+                .is_ok_and(|anim| same_frames(&old_anim, anim))
+        });
+        match matched {
+            Some(pos) => {
+                renamed_animations.push((old.clone(), added_animations.remove(pos)));
+                false
+            }
+            None => true,
+        }
+    });
+
+    CharacterDiff {
+        added_animations,
+        removed_animations,
+        renamed_animations,
+        dimensions,
+        palette_differences,
+        image_counts,
+    }
+}
+
+fn same_frames(a: &Animation, b: &Animation) -> bool {
+    a.frames.len() == b.frames.len()
+        && a.frames.iter().zip(&b.frames).all(|(fa, fb)| {
+            fa.duration_ms == fb.duration_ms
+                && fa.images.len() == fb.images.len()
+                && fa.images.iter().zip(&fb.images).all(|(ia, ib)| {
+                    ia.image_index == ib.image_index && ia.x == ib.x && ia.y == ib.y
+                })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(name: &str) -> Acs {
+        let path = format!("{}/../notes/files/{}", env!("CARGO_MANIFEST_DIR"), name);
+        Acs::new(std::fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_diff_same_character() {
+        let a = load("Bonzi.acs");
+        let mut b = load("Bonzi.acs");
+        assert!(diff(&a, &mut b).is_empty());
+    }
+
+    #[test]
+    fn test_diff_bonzi_clippit() {
+        let a = load("Bonzi.acs");
+        let mut b = load("clippit.acs");
+        let result = diff(&a, &mut b);
+
+        assert!(result.dimensions.is_some());
+        assert!(result.image_counts.is_some());
+        assert!(!result.palette_differences.is_empty());
+        assert!(!result.added_animations.is_empty());
+        assert!(!result.removed_animations.is_empty());
+        assert!(!result.to_string().is_empty());
+    }
+}
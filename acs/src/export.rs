@@ -0,0 +1,97 @@
+//! Encoders for exporting decoded images to standard file formats.
+
+#[cfg(any(feature = "png", feature = "image-crate"))]
+use crate::acs::AcsError;
+#[cfg(any(feature = "png", feature = "image-crate"))]
+use crate::acs::Image;
+
+const BMP_FILE_HEADER_SIZE: u32 = 14;
+const BMP_INFO_HEADER_SIZE: u32 = 40;
+
+/// Write an 8-bit indexed BMP.
+///
+/// `indices` must already be bottom-up with rows padded to a 4-byte boundary, which is
+/// exactly how ACS stores image data. BMP has no alpha channel, so the transparent
+/// color is only preserved as its palette entry.
+pub(crate) fn encode_bmp_indexed(
+    width: u32,
+    height: u32,
+    palette: &[[u8; 4]],
+    indices: &[u8],
+) -> Vec<u8> {
+    let palette_size = palette.len() as u32 * 4;
+    let data_offset = BMP_FILE_HEADER_SIZE + BMP_INFO_HEADER_SIZE + palette_size;
+    let file_size = data_offset + indices.len() as u32;
+
+    let mut out = Vec::with_capacity(file_size as usize);
+
+    // BITMAPFILEHEADER
+    out.extend_from_slice(b"BM");
+    out.extend_from_slice(&file_size.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes()); // reserved
+    out.extend_from_slice(&data_offset.to_le_bytes());
+
+    // BITMAPINFOHEADER (positive height = bottom-up rows)
+    out.extend_from_slice(&BMP_INFO_HEADER_SIZE.to_le_bytes());
+    out.extend_from_slice(&(width as i32).to_le_bytes());
+    out.extend_from_slice(&(height as i32).to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // planes
+    out.extend_from_slice(&8u16.to_le_bytes()); // bits per pixel
+    out.extend_from_slice(&0u32.to_le_bytes()); // BI_RGB
+    out.extend_from_slice(&(indices.len() as u32).to_le_bytes());
+    out.extend_from_slice(&2835i32.to_le_bytes()); // 72 DPI
+    out.extend_from_slice(&2835i32.to_le_bytes());
+    out.extend_from_slice(&(palette.len() as u32).to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes()); // all colors important
+
+    // Color table is RGBQUAD (B, G, R, reserved)
+    for [r, g, b, _] in palette {
+        out.extend_from_slice(&[*b, *g, *r, 0]);
+    }
+
+    out.extend_from_slice(indices);
+    out
+}
+
+#[cfg(feature = "png")]
+impl Image {
+    /// Encode the image as an RGBA8 PNG.
+    ///
+    /// Transparent pixels keep their zero alpha, so the background stays see-through.
+    pub fn to_png(&self) -> Result<Vec<u8>, AcsError> {
+        let mut out = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.data)?;
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "image-crate")]
+impl TryFrom<Image> for image::RgbaImage {
+    type Error = AcsError;
+
+    fn try_from(img: Image) -> Result<Self, AcsError> {
+        let (width, height, len) = (img.width, img.height, img.data.len());
+        image::RgbaImage::from_raw(width, height, img.data).ok_or(AcsError::InvalidImageData {
+            width,
+            height,
+            len,
+        })
+    }
+}
+
+#[cfg(feature = "image-crate")]
+impl Image {
+    /// Convert to an `image` crate buffer for further processing or saving as PNG.
+    ///
+    /// Only the PNG codec is enabled on the `image` dependency; enable more on your own
+    /// `image` dependency to save other formats.
+    pub fn to_image_buffer(&self) -> Result<image::RgbaImage, AcsError> {
+        self.clone().try_into()
+    }
+}
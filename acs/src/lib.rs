@@ -17,14 +17,56 @@
 //! let image = acs.image(0).unwrap();
 //! println!("Image: {}x{}", image.width, image.height);
 //! ```
+//!
+//! With the `log` feature enabled, the reader emits `log::trace!` records describing each
+//! parse step (offsets, sizes and entry counts). The `serde` feature derives
+//! `Serialize`/`Deserialize` for the animation and character metadata types, with GUIDs
+//! written as hex strings. The `audio-transcode` feature adds `Acs::sound_transcoded`,
+//! which compresses sounds to Ogg Opus through libopus.
+
+/// Emit a `log::trace!` record when the `log` feature is enabled; compiles to nothing
+/// otherwise.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::trace!($($arg)*)
+    };
+}
 
 mod acs;
+mod atlas;
+pub mod audio;
 mod bit_reader;
 pub mod compression;
+mod diff;
+mod export;
+pub mod lipsync;
+mod player;
 pub mod reader;
+mod render;
+#[cfg(feature = "serde")]
+mod serde_guid;
+mod timeline;
+#[cfg(feature = "audio-transcode")]
+mod transcode;
+mod validate;
+pub mod wav;
+pub mod writer;
 
 pub use acs::{
-    Acs, AcsError, Animation, Branch, CharacterInfo, Frame, FrameImage, Image, Overlay,
-    OverlayType, Sound, TransitionType,
+    Acs, AcsError, Animation, AnimationRole, Branch, CharacterInfo, ExtraData, Frame, FrameImage,
+    FullCharacterInfo, Image, ImageUsage, Overlay, OverlayType, ParseTransitionTypeError, Sound,
+    TransitionType,
 };
-pub use reader::{VoiceExtraData, VoiceInfo};
+pub use atlas::AtlasLayout;
+pub use audio::{AudioFormat, AudioSummary};
+pub use diff::{CharacterDiff, diff};
+pub use player::{DEFAULT_MIN_FRAME_MS, Player};
+// These reader types are the field types of `CharacterInfo` and `FullCharacterInfo`, so
+// they're part of the high-level API; re-exported so callers needn't reach into `reader`.
+pub use reader::{BalloonInfo, LocalizedInfo, ParseMode, TrayIcon, VoiceExtraData, VoiceInfo};
+pub use render::{DEBUG_OVERLAY_COLOR, ImageOrder, RenderOptions, nearest_index};
+pub use timeline::Timeline;
+#[cfg(feature = "audio-transcode")]
+pub use transcode::TranscodeFormat;
+pub use validate::{Severity, ValidationIssue};
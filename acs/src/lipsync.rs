@@ -0,0 +1,176 @@
+//! Helpers for choosing mouth overlays while a character speaks.
+
+use crate::acs::{Acs, AcsError, OverlayType};
+use crate::audio::downmix_pcm16;
+
+/// Default amplitude thresholds for the narrow, medium and wide tiers.
+pub const DEFAULT_THRESHOLDS: [f32; 3] = [0.05, 0.15, 0.3];
+
+/// Mouth shapes in order of openness, as selected by successive thresholds.
+const TIERS: [OverlayType; 7] = [
+    OverlayType::MouthClosed,
+    OverlayType::MouthNarrow,
+    OverlayType::MouthMedium,
+    OverlayType::MouthWide1,
+    OverlayType::MouthWide2,
+    OverlayType::MouthWide3,
+    OverlayType::MouthWide4,
+];
+
+/// Pick a mouth shape for a normalized (0.0-1.0) RMS amplitude.
+///
+/// `thresholds` should be ascending. Each threshold the amplitude reaches opens the mouth
+/// one step further: closed, narrow, medium, then the wide shapes. Extra thresholds
+/// beyond the last wide shape are ignored.
+pub fn mouth_for_amplitude(rms: f32, thresholds: &[f32]) -> OverlayType {
+    let tier = thresholds.iter().take_while(|&&t| rms >= t).count();
+    TIERS[tier.min(TIERS.len() - 1)]
+}
+
+/// `mouth_for_amplitude` with `DEFAULT_THRESHOLDS`.
+pub fn mouth_for_amplitude_default(rms: f32) -> OverlayType {
+    mouth_for_amplitude(rms, &DEFAULT_THRESHOLDS)
+}
+
+/// RMS amplitude of each `window_ms` slice of mono 16-bit PCM, normalized to 0.0-1.0.
+///
+/// A full-scale sine wave reads 1.0; louder, squarer signals are clamped there. The
+/// last window may be shorter than the rest. Feed the values to `mouth_for_amplitude`
+/// to animate a mouth to the sound.
+pub fn amplitude_envelope(pcm: &[i16], sample_rate: u32, window_ms: u32) -> Vec<f32> {
+    let window = ((sample_rate as u64 * window_ms as u64 / 1000) as usize).max(1);
+    // RMS of a full-scale sine: 32768 / sqrt(2)
+    let full_scale = 32768.0 * std::f64::consts::FRAC_1_SQRT_2;
+
+    pcm.chunks(window)
+        .map(|chunk| {
+            let sum: f64 = chunk.iter().map(|&s| s as f64 * s as f64).sum();
+            let rms = (sum / chunk.len() as f64).sqrt();
+            (rms / full_scale).min(1.0) as f32
+        })
+        .collect()
+}
+
+impl Acs {
+    /// Mouth shape for each `window_ms` of a sound, as `(start_ms, mouth)` pairs.
+    ///
+    /// Multichannel sounds are averaged to mono and mouths picked with
+    /// `DEFAULT_THRESHOLDS`. To play it back, render frames with `RenderOptions::mouth`
+    /// set to the entry for the current playback time. Only 16-bit PCM sounds are
+    /// supported.
+    pub fn lipsync_timeline(
+        &self,
+        sound_index: usize,
+        window_ms: u32,
+    ) -> Result<Vec<(u32, OverlayType)>, AcsError> {
+        let window_ms = window_ms.max(1);
+        let (format, samples) = self.sound(sound_index)?.pcm16()?;
+        let mono = downmix_pcm16(&samples, format.channels);
+
+        Ok(amplitude_envelope(&mono, format.sample_rate, window_ms)
+            .into_iter()
+            .enumerate()
+            .map(|(i, rms)| (i as u32 * window_ms, mouth_for_amplitude_default(rms)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_boundaries() {
+        let thresholds = [0.1, 0.2, 0.3];
+        assert_eq!(
+            mouth_for_amplitude(0.0, &thresholds),
+            OverlayType::MouthClosed
+        );
+        assert_eq!(
+            mouth_for_amplitude(0.099, &thresholds),
+            OverlayType::MouthClosed
+        );
+        assert_eq!(
+            mouth_for_amplitude(0.1, &thresholds),
+            OverlayType::MouthNarrow
+        );
+        assert_eq!(
+            mouth_for_amplitude(0.2, &thresholds),
+            OverlayType::MouthMedium
+        );
+        assert_eq!(
+            mouth_for_amplitude(0.3, &thresholds),
+            OverlayType::MouthWide1
+        );
+        assert_eq!(
+            mouth_for_amplitude(1.0, &thresholds),
+            OverlayType::MouthWide1
+        );
+        assert_eq!(mouth_for_amplitude(1.0, &[]), OverlayType::MouthClosed);
+    }
+
+    #[test]
+    fn test_amplitude_envelope_of_sine() {
+        let sine = |amplitude: f64| -> Vec<i16> {
+            (0..22050)
+                .map(|i| {
+                    let t = i as f64 / 22050.0;
+                    (amplitude * (2.0 * std::f64::consts::PI * 441.0 * t).sin()) as i16
+                })
+                .collect()
+        };
+
+        // 20ms windows hold whole cycles of 441Hz, so each reads the same
+        let envelope = amplitude_envelope(&sine(32767.0), 22050, 20);
+        assert_eq!(envelope.len(), 50);
+        assert!(
+            envelope.iter().all(|&a| (a - 1.0).abs() < 0.01),
+            "{:?}",
+            envelope
+        );
+
+        let quiet = amplitude_envelope(&sine(8192.0), 22050, 20);
+        assert!(quiet.iter().all(|&a| (a - 0.25).abs() < 0.01));
+
+        assert_eq!(amplitude_envelope(&[0; 300], 22050, 10), [0.0, 0.0]);
+        assert!(amplitude_envelope(&[], 22050, 10).is_empty());
+    }
+
+    #[test]
+    fn test_lipsync_timeline() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let acs = Acs::new(std::fs::read(path).unwrap()).unwrap();
+        let index = (0..acs.sound_count())
+            .find(|&i| acs.sound(i).unwrap().format().unwrap().bits_per_sample == 16)
+            .unwrap();
+
+        let timeline = acs.lipsync_timeline(index, 50).unwrap();
+        assert!(!timeline.is_empty());
+        let duration = acs.sound_duration_ms(index).unwrap();
+        assert_eq!(timeline.len() as u32, duration.div_ceil(50).max(1));
+        assert!(
+            timeline
+                .iter()
+                .enumerate()
+                .all(|(i, &(t, _))| t == i as u32 * 50)
+        );
+
+        // Sound levels vary, but some sound should open the mouth
+        let opens_mouth = (index..acs.sound_count()).any(|i| {
+            acs.lipsync_timeline(i, 50)
+                .is_ok_and(|t| t.iter().any(|&(_, m)| m != OverlayType::MouthClosed))
+        });
+        assert!(opens_mouth);
+
+        assert!(matches!(
+            acs.lipsync_timeline(acs.sound_count(), 50),
+            Err(AcsError::InvalidSoundIndex(_))
+        ));
+    }
+
+    #[test]
+    fn test_default_thresholds() {
+        assert_eq!(mouth_for_amplitude_default(0.0), OverlayType::MouthClosed);
+        assert_eq!(mouth_for_amplitude_default(1.0), OverlayType::MouthWide1);
+    }
+}
@@ -0,0 +1,270 @@
+//! Frame-by-frame animation playback.
+
+use crate::acs::{Animation, Frame};
+
+/// Default minimum effective frame duration in milliseconds.
+pub const DEFAULT_MIN_FRAME_MS: u32 = 1;
+
+/// Steps through an animation's frames in real time, following probabilistic branches.
+///
+/// The player only tracks timing and frame selection; render the current frame with
+/// `Acs::render_frame` using `current_frame_index()`.
+#[derive(Debug, Clone)]
+pub struct Player {
+    animation: Animation,
+    frame_index: usize,
+    elapsed_ms: u32,
+    min_frame_ms: u32,
+    finished: bool,
+    rng_state: u32,
+}
+
+impl Player {
+    /// Start playing an animation from its first frame.
+    pub fn new(animation: Animation) -> Self {
+        Self {
+            finished: animation.frames.is_empty(),
+            animation,
+            frame_index: 0,
+            elapsed_ms: 0,
+            min_frame_ms: DEFAULT_MIN_FRAME_MS,
+            rng_state: 0x2545_F491,
+        }
+    }
+
+    /// Set the minimum effective frame duration.
+    ///
+    /// Some frames have a duration of 0. Agent advanced past those on its next timer
+    /// tick, whereas a naive player would spin on them, so playback treats them as lasting
+    /// at least this long. `Frame::duration_ms` keeps the raw value for exporters.
+    ///
+    /// Values below 1 are raised to 1, so `advance` always consumes time on each frame
+    /// and can't loop forever on zero-length frames that branch back to themselves.
+    pub fn with_min_frame_ms(mut self, min_frame_ms: u32) -> Self {
+        self.min_frame_ms = min_frame_ms.max(1);
+        self
+    }
+
+    /// Seed the generator used to pick probabilistic branches.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        // Xorshift gets stuck on a zero state
+        self.rng_state = seed.max(1);
+        self
+    }
+
+    /// The animation being played.
+    pub fn animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    /// The current frame.
+    pub fn current_frame(&self) -> Option<&Frame> {
+        self.animation.frames.get(self.frame_index)
+    }
+
+    /// Index of the current frame within the animation.
+    pub fn current_frame_index(&self) -> usize {
+        self.frame_index
+    }
+
+    /// Whether playback has run past the last frame.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Duration of a frame as used for playback, with the minimum applied.
+    pub fn effective_duration_ms(&self, frame_index: usize) -> u32 {
+        self.animation
+            .frames
+            .get(frame_index)
+            .map(|f| f.duration_ms.max(self.min_frame_ms))
+            .unwrap_or(0)
+    }
+
+    /// Advance playback by `delta_ms`, moving through as many frames as have elapsed.
+    ///
+    /// Returns true if playback moved off the frame it was on.
+    pub fn advance(&mut self, delta_ms: u32) -> bool {
+        let mut changed = false;
+        self.elapsed_ms = self.elapsed_ms.saturating_add(delta_ms);
+
+        while !self.finished {
+            let duration = self.effective_duration_ms(self.frame_index);
+            if self.elapsed_ms < duration {
+                break;
+            }
+            self.elapsed_ms -= duration;
+            self.next_frame();
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// Move to the next frame immediately, taking a branch if one is chosen.
+    pub fn next_frame(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        let roll = self.next_random() % 100;
+        let frame = &self.animation.frames[self.frame_index];
+
+        // Branch probabilities are percentages; the remainder falls through to the next frame
+        // Summed as u32 so out-of-range probabilities in a malformed file can't overflow
+        let mut cumulative = 0u32;
+        let branch = frame.branches.iter().find(|b| {
+            cumulative += u32::from(b.probability);
+            roll < cumulative
+        });
+
+        match branch {
+            Some(b) if b.frame_index < self.animation.frames.len() => {
+                self.frame_index = b.frame_index;
+            }
+            _ if self.frame_index + 1 < self.animation.frames.len() => {
+                self.frame_index += 1;
+            }
+            _ => {
+                self.finished = true;
+            }
+        }
+    }
+
+    /// Move to the previous frame in linear order, stopping at frame 0.
+    ///
+    /// This is meant for scrubbing: branches and returns taken on the way forward are not
+    /// reversed, so stepping back after a branch doesn't retrace the path that was played.
+    pub fn step_back(&mut self) {
+        if self.animation.frames.is_empty() {
+            return;
+        }
+        self.frame_index = self.frame_index.saturating_sub(1);
+        self.elapsed_ms = 0;
+        self.finished = false;
+    }
+
+    fn next_random(&mut self) -> u32 {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng_state = x;
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acs::{Acs, Branch, TransitionType};
+
+    fn load_animation(file: &str, name: &str) -> Animation {
+        let path = format!("{}/../notes/files/{}", env!("CARGO_MANIFEST_DIR"), file);
+        let mut acs = Acs::new(std::fs::read(path).unwrap()).unwrap();
+        acs.animation(name).unwrap().clone()
+    }
+
+    #[test]
+    fn test_min_frame_duration() {
+        let animation = load_animation("clippit.acs", "IdleSideToSide");
+        let zero_frame = animation
+            .frames
+            .iter()
+            .position(|f| f.duration_ms == 0)
+            .expect("animation has a zero-duration frame");
+
+        let player = Player::new(animation.clone());
+        assert_eq!(
+            player.effective_duration_ms(zero_frame),
+            DEFAULT_MIN_FRAME_MS
+        );
+        assert_eq!(player.animation().frames[zero_frame].duration_ms, 0);
+
+        let player = Player::new(animation).with_min_frame_ms(100);
+        assert_eq!(player.effective_duration_ms(zero_frame), 100);
+    }
+
+    #[test]
+    fn test_step_back() {
+        let animation = load_animation("Bonzi.acs", "Explain");
+        assert!(animation.frames[0].branches.is_empty());
+
+        let mut player = Player::new(animation);
+        player.step_back();
+        assert_eq!(player.current_frame_index(), 0);
+
+        player.next_frame();
+        assert_eq!(player.current_frame_index(), 1);
+        player.step_back();
+        assert_eq!(player.current_frame_index(), 0);
+    }
+
+    #[test]
+    fn test_advance_terminates() {
+        let animation = load_animation("Bonzi.acs", "Explain");
+        let total = animation.total_duration_ms() + animation.frames.len() as u32;
+        let mut player = Player::new(animation);
+        player.advance(total);
+        assert!(player.is_finished());
+    }
+
+    fn frame(duration_ms: u32, branches: Vec<Branch>) -> Frame {
+        Frame {
+            images: Vec::new(),
+            duration_ms,
+            sound_index: None,
+            exit_branch: None,
+            branches,
+            overlays: Vec::new(),
+        }
+    }
+
+    fn animation(frames: Vec<Frame>) -> Animation {
+        Animation {
+            name: "Test".to_string(),
+            frames,
+            return_animation: None,
+            transition_type: TransitionType::None,
+        }
+    }
+
+    #[test]
+    fn test_zero_length_self_branch_terminates() {
+        let always_loop = vec![Branch {
+            frame_index: 0,
+            probability: 100,
+        }];
+        let animation = animation(vec![frame(0, always_loop)]);
+        let mut player = Player::new(animation).with_min_frame_ms(0);
+        assert_eq!(player.effective_duration_ms(0), 1);
+
+        assert!(player.advance(1000));
+        assert_eq!(player.current_frame_index(), 0);
+        assert!(!player.is_finished());
+    }
+
+    #[test]
+    fn test_out_of_range_probabilities() {
+        let branches = vec![
+            Branch {
+                frame_index: 1,
+                probability: u16::MAX,
+            },
+            Branch {
+                frame_index: 2,
+                probability: u16::MAX,
+            },
+        ];
+        let animation = animation(vec![
+            frame(10, branches),
+            frame(10, vec![]),
+            frame(10, vec![]),
+        ]);
+        for seed in 1..50 {
+            let mut player = Player::new(animation.clone()).with_seed(seed);
+            player.next_frame();
+            assert_eq!(player.current_frame_index(), 1);
+        }
+    }
+}
@@ -3,25 +3,47 @@
 //! Provides zero-copy parsing of raw ACS file structures.
 
 use std::fmt;
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Seek, SeekFrom};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ReaderError {
     UnexpectedEof,
     InvalidSignature(u32),
-    InvalidOffset { offset: u32, size: u32 },
+    InvalidOffset {
+        offset: u32,
+        size: u32,
+    },
     InvalidUtf16,
+    /// A reserved byte that's always been observed as zero wasn't, under `ParseMode::Strict`
+    UnexpectedReserved {
+        offset: u64,
+        value: u8,
+    },
 }
 
 impl fmt::Display for ReaderError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::UnexpectedEof => write!(f, "unexpected end of file"),
-            Self::InvalidSignature(sig) => write!(f, "invalid signature: 0x{:08X}", sig),
+            Self::InvalidSignature(sig) => {
+                write!(f, "invalid signature: 0x{:08X}", sig)?;
+                if sig.swap_bytes() == ACS_SIGNATURE {
+                    write!(
+                        f,
+                        " (byte-swapped ACS signature; file may be big-endian or corrupted)"
+                    )?;
+                }
+                Ok(())
+            }
             Self::InvalidOffset { offset, size } => {
                 write!(f, "invalid offset {} with size {}", offset, size)
             }
             Self::InvalidUtf16 => write!(f, "invalid UTF-16 string"),
+            Self::UnexpectedReserved { offset, value } => write!(
+                f,
+                "reserved byte at offset {} is 0x{:02X}, expected 0 (unknown format variant?)",
+                offset, value
+            ),
         }
     }
 }
@@ -30,12 +52,30 @@ impl std::error::Error for ReaderError {}
 
 pub const ACS_SIGNATURE: u32 = 0xABCDABC3;
 
+/// Character flag marking that voice info follows (bit 5, not bit 4 as the spec says).
+pub(crate) const FLAG_VOICE_OUTPUT: u32 = 0x20;
+/// Character flag set by every known file, which all store balloon info.
+pub(crate) const FLAG_BALLOON_ENABLED: u32 = 0x200;
+/// Character flag we take to mean the balloon info is left out, unless
+/// `FLAG_BALLOON_ENABLED` is also set.
+pub(crate) const FLAG_BALLOON_DISABLED: u32 = 0x100;
+
 #[derive(Debug, Clone)]
 pub struct Locator {
     pub offset: u32,
     pub size: u32,
 }
 
+impl Locator {
+    /// Whether this locator points at nothing.
+    ///
+    /// Stripped files may zero out sections they don't include; offset 0 is always the
+    /// header, so a zero offset can never be a valid section either.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0 || self.offset == 0
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AcsHeader {
     pub signature: u32,
@@ -54,8 +94,11 @@ pub struct LocalizedInfo {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VoiceInfo {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_guid"))]
     pub tts_engine_id: [u8; 16],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_guid"))]
     pub tts_mode_id: [u8; 16],
     pub speed: u32,
     pub pitch: u16,
@@ -64,6 +107,7 @@ pub struct VoiceInfo {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VoiceExtraData {
     pub lang_id: u16,
     pub lang_dialect: String,
@@ -72,6 +116,10 @@ pub struct VoiceExtraData {
     pub style: String,
 }
 
+/// Word balloon settings.
+///
+/// Balloon colors are `[r, g, b]`. Unlike the palette, they're stored as Windows
+/// `COLORREF` values (R, G, B, 0 in file order), not `RGBQUAD`s.
 #[derive(Debug, Clone)]
 pub struct BalloonInfo {
     pub num_lines: u8,
@@ -111,7 +159,8 @@ pub struct RawCharacterInfo {
     pub anim_set_major_version: u16,
     pub anim_set_minor_version: u16,
     pub voice_info: Option<VoiceInfo>,
-    pub balloon_info: BalloonInfo,
+    /// `None` if the flags say the balloon info was left out
+    pub balloon_info: Option<BalloonInfo>,
     pub palette: Vec<[u8; 3]>,
     pub tray_icon: Option<TrayIcon>,
     pub states: Vec<StateInfo>,
@@ -123,6 +172,14 @@ pub struct AnimationEntry {
     pub locator: Locator,
 }
 
+#[derive(Debug, Clone)]
+pub struct RawAnimationHeader {
+    pub name: String,
+    pub transition_type: u8,
+    pub return_animation: String,
+    pub frame_count: u16,
+}
+
 #[derive(Debug, Clone)]
 pub struct RawAnimationInfo {
     pub name: String,
@@ -159,6 +216,8 @@ pub struct RawOverlayInfo {
     pub overlay_type: u8,
     pub replace_enabled: bool,
     pub image_index: u16,
+    /// Undocumented byte following the image index (observed: 0x00).
+    pub unknown: u8,
     pub x_offset: i16,
     pub y_offset: i16,
     pub width: u16,
@@ -187,17 +246,137 @@ pub struct AudioEntry {
     pub checksum: u32,
 }
 
-pub struct AcsReader<'a> {
-    cursor: Cursor<&'a [u8]>,
+/// How the reader treats fields whose meaning is unknown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Accept any value, as Agent itself appears to.
+    #[default]
+    Lenient,
+    /// Error on values other than those observed in known files, so a file with a
+    /// different layout fails loudly instead of parsing into garbage.
+    Strict,
 }
 
-impl<'a> AcsReader<'a> {
-    pub fn new(data: &'a [u8]) -> Self {
+/// Little-endian ACS primitives and positioning, for any `Read + Seek` source.
+///
+/// `AcsReader` delegates its primitive reads and seeks here. The section parsers stay
+/// on `AcsReader`, since they bounds-check locators against the whole file.
+pub(crate) trait AcsRead: Read + Seek {
+    /// Move to an absolute offset. Seeking past the end succeeds; the next read fails.
+    fn seek_to(&mut self, offset: u64) -> Result<(), ReaderError> {
+        self.seek(SeekFrom::Start(offset))
+            .map_err(|_| ReaderError::UnexpectedEof)?;
+        Ok(())
+    }
+
+    /// Run `read` and seek back to where it started, whether or not it succeeds.
+    fn restoring<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, ReaderError>,
+    ) -> Result<T, ReaderError> {
+        let pos = self
+            .stream_position()
+            .map_err(|_| ReaderError::UnexpectedEof)?;
+        let result = read(self);
+        self.seek_to(pos)?;
+        result
+    }
+
+    fn read_u8(&mut self) -> Result<u8, ReaderError> {
+        Ok(self.read_fixed::<1>()?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, ReaderError> {
+        Ok(u16::from_le_bytes(self.read_fixed()?))
+    }
+
+    fn read_i16(&mut self) -> Result<i16, ReaderError> {
+        Ok(i16::from_le_bytes(self.read_fixed()?))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ReaderError> {
+        Ok(u32::from_le_bytes(self.read_fixed()?))
+    }
+
+    fn read_i32(&mut self) -> Result<i32, ReaderError> {
+        Ok(i32::from_le_bytes(self.read_fixed()?))
+    }
+
+    fn read_fixed<const N: usize>(&mut self) -> Result<[u8; N], ReaderError> {
+        let mut buf = [0u8; N];
+        self.read_exact(&mut buf)
+            .map_err(|_| ReaderError::UnexpectedEof)?;
+        Ok(buf)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, ReaderError> {
+        let mut buf = vec![0u8; len];
+        self.read_exact(&mut buf)
+            .map_err(|_| ReaderError::UnexpectedEof)?;
+        Ok(buf)
+    }
+
+    fn read_guid(&mut self) -> Result<[u8; 16], ReaderError> {
+        self.read_fixed()
+    }
+
+    /// Read a length-prefixed UTF-16LE string.
+    ///
+    /// ACS format: length (character count, not including terminator) followed by
+    /// that many UTF-16LE characters plus a null terminator (0x0000).
+    fn read_string(&mut self) -> Result<String, ReaderError> {
+        let len = self.read_u32()? as usize;
+        if len == 0 {
+            return Ok(String::new());
+        }
+        // Read len characters + 1 null terminator
+        let bytes = self.read_bytes((len + 1) * 2)?;
+        // Parse only the actual characters (exclude the null terminator)
+        let utf16: Vec<u16> = bytes[..len * 2]
+            .chunks_exact(2)
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect();
+        String::from_utf16(&utf16).map_err(|_| ReaderError::InvalidUtf16)
+    }
+}
+
+impl<R: Read + Seek> AcsRead for R {}
+
+/// Parser for ACS sections, over anything that holds the file's bytes: a borrowed
+/// slice, or a shared buffer as `Acs` keeps.
+pub struct AcsReader<D> {
+    cursor: Cursor<D>,
+    mode: ParseMode,
+}
+
+impl<D: AsRef<[u8]>> Read for AcsReader<D> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.cursor.read(buf)
+    }
+}
+
+impl<D: AsRef<[u8]>> Seek for AcsReader<D> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.cursor.seek(pos)
+    }
+}
+
+impl<D: AsRef<[u8]>> AcsReader<D> {
+    pub fn new(data: D) -> Self {
+        Self::with_mode(data, ParseMode::default())
+    }
+
+    pub fn with_mode(data: D, mode: ParseMode) -> Self {
         Self {
             cursor: Cursor::new(data),
+            mode,
         }
     }
 
+    pub fn mode(&self) -> ParseMode {
+        self.mode
+    }
+
     pub fn position(&self) -> u64 {
         self.cursor.position()
     }
@@ -207,67 +386,55 @@ impl<'a> AcsReader<'a> {
     }
 
     pub fn len(&self) -> usize {
-        self.cursor.get_ref().len()
+        self.cursor.get_ref().as_ref().len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.cursor.get_ref().is_empty()
+        self.len() == 0
+    }
+
+    /// The data this reader parses.
+    pub fn get_ref(&self) -> &D {
+        self.cursor.get_ref()
+    }
+
+    /// Run `read` and put the cursor back where it was, whether or not it succeeds.
+    ///
+    /// Lets one reader serve scattered random-access reads without the caller tracking
+    /// its position.
+    pub fn read_at<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, ReaderError>,
+    ) -> Result<T, ReaderError> {
+        self.restoring(read)
     }
 
     pub fn read_u8(&mut self) -> Result<u8, ReaderError> {
-        let mut buf = [0u8; 1];
-        self.cursor
-            .read_exact(&mut buf)
-            .map_err(|_| ReaderError::UnexpectedEof)?;
-        Ok(buf[0])
+        self.cursor.read_u8()
     }
 
     pub fn read_u16(&mut self) -> Result<u16, ReaderError> {
-        let mut buf = [0u8; 2];
-        self.cursor
-            .read_exact(&mut buf)
-            .map_err(|_| ReaderError::UnexpectedEof)?;
-        Ok(u16::from_le_bytes(buf))
+        self.cursor.read_u16()
     }
 
     pub fn read_i16(&mut self) -> Result<i16, ReaderError> {
-        let mut buf = [0u8; 2];
-        self.cursor
-            .read_exact(&mut buf)
-            .map_err(|_| ReaderError::UnexpectedEof)?;
-        Ok(i16::from_le_bytes(buf))
+        self.cursor.read_i16()
     }
 
     pub fn read_u32(&mut self) -> Result<u32, ReaderError> {
-        let mut buf = [0u8; 4];
-        self.cursor
-            .read_exact(&mut buf)
-            .map_err(|_| ReaderError::UnexpectedEof)?;
-        Ok(u32::from_le_bytes(buf))
+        self.cursor.read_u32()
     }
 
     pub fn read_i32(&mut self) -> Result<i32, ReaderError> {
-        let mut buf = [0u8; 4];
-        self.cursor
-            .read_exact(&mut buf)
-            .map_err(|_| ReaderError::UnexpectedEof)?;
-        Ok(i32::from_le_bytes(buf))
+        self.cursor.read_i32()
     }
 
     pub fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, ReaderError> {
-        let mut buf = vec![0u8; len];
-        self.cursor
-            .read_exact(&mut buf)
-            .map_err(|_| ReaderError::UnexpectedEof)?;
-        Ok(buf)
+        self.cursor.read_bytes(len)
     }
 
     pub fn read_guid(&mut self) -> Result<[u8; 16], ReaderError> {
-        let mut guid = [0u8; 16];
-        self.cursor
-            .read_exact(&mut guid)
-            .map_err(|_| ReaderError::UnexpectedEof)?;
-        Ok(guid)
+        self.cursor.read_guid()
     }
 
     /// Read a length-prefixed UTF-16LE string.
@@ -275,18 +442,21 @@ impl<'a> AcsReader<'a> {
     /// ACS format: length (character count, not including terminator) followed by
     /// that many UTF-16LE characters plus a null terminator (0x0000).
     pub fn read_string(&mut self) -> Result<String, ReaderError> {
-        let len = self.read_u32()? as usize;
-        if len == 0 {
-            return Ok(String::new());
+        self.cursor.read_string()
+    }
+
+    /// Check that a section lies within the data.
+    ///
+    /// Only the end of the section is checked, not that the file ends there: some tools
+    /// append padding or a signature after the last section, which is harmless.
+    fn check_fits(&self, locator: &Locator) -> Result<(), ReaderError> {
+        if locator.offset as u64 + locator.size as u64 > self.len() as u64 {
+            return Err(ReaderError::InvalidOffset {
+                offset: locator.offset,
+                size: locator.size,
+            });
         }
-        // Read len characters + 1 null terminator
-        let bytes = self.read_bytes((len + 1) * 2)?;
-        // Parse only the actual characters (exclude the null terminator)
-        let utf16: Vec<u16> = bytes[..len * 2]
-            .chunks_exact(2)
-            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
-            .collect();
-        String::from_utf16(&utf16).map_err(|_| ReaderError::InvalidUtf16)
+        Ok(())
     }
 
     fn read_locator(&mut self) -> Result<Locator, ReaderError> {
@@ -302,16 +472,23 @@ impl<'a> AcsReader<'a> {
             return Err(ReaderError::InvalidSignature(signature));
         }
 
-        Ok(AcsHeader {
+        let header = AcsHeader {
             signature,
             character_info: self.read_locator()?,
             animation_info: self.read_locator()?,
             image_info: self.read_locator()?,
             audio_info: self.read_locator()?,
-        })
+        };
+
+        trace!(
+            "header: character info {:?}, animations {:?}, images {:?}, audio {:?}",
+            header.character_info, header.animation_info, header.image_info, header.audio_info
+        );
+        Ok(header)
     }
 
     pub fn read_character_info(&mut self, offset: u32) -> Result<RawCharacterInfo, ReaderError> {
+        trace!("character info at offset {}", offset);
         self.seek(offset as u64);
 
         let minor_version = self.read_u16()?;
@@ -331,15 +508,21 @@ impl<'a> AcsReader<'a> {
         let anim_set_minor_version = self.read_u16()?;
 
         // Voice info is present only if bit 5 is set (not bit 4 as spec says)
-        // Bit 5 = 0x20
-        let voice_info = if flags & 0x20 != 0 {
+        let voice_info = if flags & FLAG_VOICE_OUTPUT != 0 {
             Some(self.read_voice_info()?)
         } else {
             None
         };
 
-        // Balloon info is always present
-        let balloon_info = self.read_balloon_info()?;
+        // Every known file sets bit 9 and stores balloon info. The spec's flag numbering
+        // is off by one for voice, so which bit governs the balloon isn't certain; treat
+        // it as absent only when bit 8 is set without bit 9, and present otherwise.
+        let balloon_info =
+            if flags & FLAG_BALLOON_DISABLED != 0 && flags & FLAG_BALLOON_ENABLED == 0 {
+                None
+            } else {
+                Some(self.read_balloon_info()?)
+            };
 
         // Palette (count is ULONG, each color is RGBQUAD = 4 bytes)
         // RGBQUAD in Windows is stored as: Blue, Green, Red, Reserved (BGR order)
@@ -455,7 +638,8 @@ impl<'a> AcsReader<'a> {
     fn read_balloon_info(&mut self) -> Result<BalloonInfo, ReaderError> {
         let num_lines = self.read_u8()?;
         let chars_per_line = self.read_u8()?;
-        // Colors are RGBQUAD (4 bytes each: R, G, B, Reserved)
+        // Colors are COLORREFs (R, G, B, 0), not BGR RGBQUADs like the palette. The stock
+        // background reads as the Windows tooltip color #FFFFE1 in this order.
         let fg_color = [self.read_u8()?, self.read_u8()?, self.read_u8()?];
         let _fg_reserved = self.read_u8()?;
         let bg_color = [self.read_u8()?, self.read_u8()?, self.read_u8()?];
@@ -508,8 +692,17 @@ impl<'a> AcsReader<'a> {
         &mut self,
         locator: &Locator,
     ) -> Result<Vec<AnimationEntry>, ReaderError> {
+        if locator.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.check_fits(locator)?;
+
         self.seek(locator.offset as u64);
         let count = self.read_u32()? as usize;
+        trace!(
+            "animation list at offset {} ({} bytes): {} entries",
+            locator.offset, locator.size, count
+        );
         let mut entries = Vec::with_capacity(count);
 
         for _ in 0..count {
@@ -524,29 +717,47 @@ impl<'a> AcsReader<'a> {
         Ok(entries)
     }
 
-    pub fn read_animation_info(&mut self, offset: u32) -> Result<RawAnimationInfo, ReaderError> {
+    /// Read an animation's name, transition and frame count without parsing its frames.
+    ///
+    /// Leaves the cursor just past the frame count, where the first frame begins, so
+    /// indexing tools can build an animation table cheaply and resume a full read later.
+    pub fn read_animation_header(
+        &mut self,
+        offset: u32,
+    ) -> Result<RawAnimationHeader, ReaderError> {
         self.seek(offset as u64);
 
-        let name = self.read_string()?;
-        let transition_type = self.read_u8()?;
-        let return_animation = self.read_string()?;
+        Ok(RawAnimationHeader {
+            name: self.read_string()?,
+            transition_type: self.read_u8()?,
+            return_animation: self.read_string()?,
+            frame_count: self.read_u16()?,
+        })
+    }
 
-        let frame_count = self.read_u16()? as usize;
-        let mut frames = Vec::with_capacity(frame_count);
+    pub fn read_animation_info(&mut self, offset: u32) -> Result<RawAnimationInfo, ReaderError> {
+        let header = self.read_animation_header(offset)?;
 
-        for _ in 0..frame_count {
+        let mut frames = Vec::with_capacity(header.frame_count as usize);
+        for _ in 0..header.frame_count {
             frames.push(self.read_frame_info()?);
         }
 
         Ok(RawAnimationInfo {
-            name,
-            transition_type,
-            return_animation,
+            name: header.name,
+            transition_type: header.transition_type,
+            return_animation: header.return_animation,
             frames,
         })
     }
 
-    fn read_frame_info(&mut self) -> Result<RawFrameInfo, ReaderError> {
+    /// Like `read_animation_info`, but restores the cursor afterwards (see `read_at`).
+    pub fn read_animation_info_at(&mut self, offset: u32) -> Result<RawAnimationInfo, ReaderError> {
+        self.read_at(|reader| reader.read_animation_info(offset))
+    }
+
+    /// Read one frame at the cursor, e.g. after `read_animation_header` or a previous frame.
+    pub fn read_frame_info(&mut self) -> Result<RawFrameInfo, ReaderError> {
         // Frame images
         let image_count = self.read_u16()? as usize;
         let mut images = Vec::with_capacity(image_count);
@@ -593,7 +804,14 @@ impl<'a> AcsReader<'a> {
         let overlay_type = self.read_u8()?;
         let replace_enabled = self.read_u8()? != 0;
         let image_index = self.read_u16()?;
-        let _unknown = self.read_u8()?; // Unknown byte (observed: 0x00)
+        let unknown_offset = self.position();
+        let unknown = self.read_u8()?; // Unknown byte (observed: 0x00)
+        if self.mode == ParseMode::Strict && unknown != 0 {
+            return Err(ReaderError::UnexpectedReserved {
+                offset: unknown_offset,
+                value: unknown,
+            });
+        }
         let has_region = self.read_u8()? != 0;
         let x_offset = self.read_i16()?;
         let y_offset = self.read_i16()?;
@@ -611,6 +829,7 @@ impl<'a> AcsReader<'a> {
             overlay_type,
             replace_enabled,
             image_index,
+            unknown,
             x_offset,
             y_offset,
             width,
@@ -620,8 +839,17 @@ impl<'a> AcsReader<'a> {
     }
 
     pub fn read_image_list(&mut self, locator: &Locator) -> Result<Vec<ImageEntry>, ReaderError> {
+        if locator.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.check_fits(locator)?;
+
         self.seek(locator.offset as u64);
         let count = self.read_u32()? as usize;
+        trace!(
+            "image list at offset {} ({} bytes): {} entries",
+            locator.offset, locator.size, count
+        );
         let mut entries = Vec::with_capacity(count);
 
         for _ in 0..count {
@@ -634,13 +862,22 @@ impl<'a> AcsReader<'a> {
         Ok(entries)
     }
 
-    pub fn read_image_info(&mut self, offset: u32) -> Result<RawImageInfo, ReaderError> {
+    /// Read an image's `(width, height, is_compressed)` header without its pixel data.
+    ///
+    /// Leaves the cursor just past the header, at the compressed size (if compressed) or
+    /// the pixel data, so building a dimension table is a seek and six bytes per image.
+    pub fn read_image_header(&mut self, offset: u32) -> Result<(u16, u16, bool), ReaderError> {
         self.seek(offset as u64);
 
         let _unknown = self.read_u8()?;
         let width = self.read_u16()?;
         let height = self.read_u16()?;
         let is_compressed = self.read_u8()? != 0;
+        Ok((width, height, is_compressed))
+    }
+
+    pub fn read_image_info(&mut self, offset: u32) -> Result<RawImageInfo, ReaderError> {
+        let (width, height, is_compressed) = self.read_image_header(offset)?;
 
         // Calculate padded row width (DWORD aligned)
         let row_width = (width as usize + 3) & !3;
@@ -672,9 +909,23 @@ impl<'a> AcsReader<'a> {
         })
     }
 
+    /// Like `read_image_info`, but restores the cursor afterwards (see `read_at`).
+    pub fn read_image_info_at(&mut self, offset: u32) -> Result<RawImageInfo, ReaderError> {
+        self.read_at(|reader| reader.read_image_info(offset))
+    }
+
     pub fn read_audio_list(&mut self, locator: &Locator) -> Result<Vec<AudioEntry>, ReaderError> {
+        if locator.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.check_fits(locator)?;
+
         self.seek(locator.offset as u64);
         let count = self.read_u32()? as usize;
+        trace!(
+            "audio list at offset {} ({} bytes): {} entries",
+            locator.offset, locator.size, count
+        );
         let mut entries = Vec::with_capacity(count);
 
         for _ in 0..count {
@@ -707,6 +958,49 @@ mod tests {
         assert_eq!(reader.read_u32().unwrap(), 0x07060504); // bytes [0x04, 0x05, 0x06, 0x07] -> 0x07060504 LE
     }
 
+    #[test]
+    fn test_strict_overlay_unknown_byte() {
+        let overlay = |unknown: u8| {
+            let mut data = vec![1, 0, 5, 0, unknown, 0];
+            data.extend([0; 8]); // x, y, width, height
+            data
+        };
+
+        let data = overlay(0);
+        let mut reader = AcsReader::with_mode(&data, ParseMode::Strict);
+        assert_eq!(reader.read_overlay_info().unwrap().image_index, 5);
+
+        let data = overlay(7);
+        let mut lenient = AcsReader::new(&data);
+        assert_eq!(lenient.read_overlay_info().unwrap().unknown, 7);
+        let mut strict = AcsReader::with_mode(&data, ParseMode::Strict);
+        assert_eq!(
+            strict.read_overlay_info().unwrap_err(),
+            ReaderError::UnexpectedReserved {
+                offset: 4,
+                value: 7
+            }
+        );
+    }
+
+    #[test]
+    fn test_acs_read_over_cursors() {
+        // A string and a u16, read from a borrowed slice and an owned buffer
+        let data = [0x01, 0x00, 0x00, 0x00, 0x41, 0x00, 0x00, 0x00, 0x34, 0x12];
+
+        let mut slice = Cursor::new(&data[..]);
+        let string = slice.restoring(|r| r.read_string()).unwrap();
+        assert_eq!(string, "A");
+        assert_eq!(slice.position(), 0);
+        slice.seek_to(8).unwrap();
+        assert_eq!(slice.read_u16().unwrap(), 0x1234);
+        assert!(matches!(slice.read_u8(), Err(ReaderError::UnexpectedEof)));
+
+        let mut owned = Cursor::new(data.to_vec());
+        assert_eq!(owned.read_string().unwrap(), "A");
+        assert_eq!(owned.read_u16().unwrap(), 0x1234);
+    }
+
     #[test]
     fn test_read_string() {
         // Length (4 bytes LE) + UTF-16LE "Hi" + null terminator
@@ -720,10 +1014,176 @@ mod tests {
         assert_eq!(reader.read_string().unwrap(), "Hi");
     }
 
+    #[test]
+    fn test_read_animation_header() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let data = std::fs::read(path).unwrap();
+        let mut reader = AcsReader::new(&data);
+        let header = reader.read_header().unwrap();
+        let entries = reader.read_animation_list(&header.animation_info).unwrap();
+        let offset = entries[0].locator.offset;
+
+        let anim_header = reader.read_animation_header(offset).unwrap();
+        assert!(anim_header.name.eq_ignore_ascii_case(&entries[0].name));
+
+        // Continuing from the cursor should parse the first frame
+        let first_frame = reader.read_frame_info().unwrap();
+
+        let full = reader.read_animation_info(offset).unwrap();
+        assert_eq!(anim_header.frame_count as usize, full.frames.len());
+        assert_eq!(anim_header.transition_type, full.transition_type);
+        assert_eq!(anim_header.return_animation, full.return_animation);
+        assert_eq!(first_frame.duration, full.frames[0].duration);
+        assert_eq!(first_frame.images.len(), full.frames[0].images.len());
+    }
+
+    #[test]
+    fn test_empty_locator_lists() {
+        let data = [0xFFu8; 16];
+        let mut reader = AcsReader::new(&data);
+        let empty = Locator { offset: 0, size: 0 };
+        assert!(reader.read_animation_list(&empty).unwrap().is_empty());
+        assert!(reader.read_image_list(&empty).unwrap().is_empty());
+        assert!(reader.read_audio_list(&empty).unwrap().is_empty());
+
+        let zero_size = Locator { offset: 4, size: 0 };
+        assert!(reader.read_audio_list(&zero_size).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_byte_swapped_signature_hint() {
+        let data = ACS_SIGNATURE.to_be_bytes();
+        let mut reader = AcsReader::new(&data);
+        let err = reader.read_header().unwrap_err();
+        assert_eq!(
+            err,
+            ReaderError::InvalidSignature(ACS_SIGNATURE.swap_bytes())
+        );
+        assert!(err.to_string().contains("byte-swapped"));
+
+        let err = ReaderError::InvalidSignature(0x12345678);
+        assert!(!err.to_string().contains("byte-swapped"));
+    }
+
+    #[test]
+    fn test_balloon_color_order() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/clippit.acs");
+        let data = std::fs::read(path).unwrap();
+        let mut reader = AcsReader::new(&data);
+        let header = reader.read_header().unwrap();
+        let info = reader
+            .read_character_info(header.character_info.offset)
+            .unwrap();
+
+        // Black text on the standard tooltip yellow
+        let balloon = info.balloon_info.unwrap();
+        assert_eq!(balloon.fg_color, [0, 0, 0]);
+        assert_eq!(balloon.bg_color, [0xFF, 0xFF, 0xE1]);
+        assert_eq!(balloon.border_color, [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_character_without_balloon() {
+        use crate::writer::AcsBuilder;
+
+        let palette = vec![[0, 0, 0], [255, 255, 255]];
+        let with = AcsBuilder::new(4, 4).palette(palette.clone()).build();
+        let without = AcsBuilder::new(4, 4)
+            .palette(palette)
+            .balloon(false)
+            .build();
+
+        let read = |data: &[u8]| {
+            let mut reader = AcsReader::new(data);
+            let header = reader.read_header().unwrap();
+            let info = reader
+                .read_character_info(header.character_info.offset)
+                .unwrap();
+            (info, header.character_info.size)
+        };
+        let (with_info, with_size) = read(&with);
+        let (without_info, without_size) = read(&without);
+
+        assert!(with_info.balloon_info.is_some());
+        assert!(without_info.balloon_info.is_none());
+        assert!(without_size < with_size);
+        // The palette after the (missing) balloon still lines up
+        assert_eq!(without_info.palette, with_info.palette);
+    }
+
     #[test]
     fn test_unexpected_eof() {
         let data = [0x01, 0x02];
         let mut reader = AcsReader::new(&data);
         assert!(reader.read_u32().is_err());
     }
+
+    #[test]
+    fn test_read_image_header() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let data = std::fs::read(path).unwrap();
+        let mut reader = AcsReader::new(&data);
+        let header = reader.read_header().unwrap();
+        let offset = reader.read_image_list(&header.image_info).unwrap()[0]
+            .locator
+            .offset;
+
+        let (width, height, compressed) = reader.read_image_header(offset).unwrap();
+        assert_eq!(reader.position(), offset as u64 + 6);
+
+        let info = reader.read_image_info(offset).unwrap();
+        assert_eq!(
+            (width, height, compressed),
+            (info.width, info.height, info.is_compressed)
+        );
+    }
+
+    #[test]
+    fn test_read_at_restores_cursor() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let data = std::fs::read(path).unwrap();
+        let mut reader = AcsReader::new(&data);
+        let header = reader.read_header().unwrap();
+        let animations = reader.read_animation_list(&header.animation_info).unwrap();
+        let images = reader.read_image_list(&header.image_info).unwrap();
+        let pos = reader.position();
+
+        let image = reader.read_image_info_at(images[1].locator.offset).unwrap();
+        assert_eq!(reader.position(), pos);
+        let animation = reader
+            .read_animation_info_at(animations[0].locator.offset)
+            .unwrap();
+        assert_eq!(reader.position(), pos);
+
+        let mut fresh = AcsReader::new(&data);
+        let expected = fresh.read_image_info(images[1].locator.offset).unwrap();
+        assert_eq!(
+            (image.width, image.height),
+            (expected.width, expected.height)
+        );
+        assert_eq!(image.data, expected.data);
+        assert!(animation.name.eq_ignore_ascii_case(&animations[0].name));
+
+        assert!(reader.read_image_info_at(data.len() as u32).is_err());
+        assert_eq!(reader.position(), pos);
+    }
+
+    #[test]
+    fn test_trailing_data_after_sections() {
+        use crate::writer::AcsBuilder;
+
+        let mut data = AcsBuilder::new(1, 1).palette(vec![[0, 0, 0]]).build();
+        let len = data.len();
+        data.extend_from_slice(&[0xAB; 100]);
+        assert!(crate::Acs::new(data.clone()).is_ok());
+
+        // Truncating into the last section (the audio list) is still caught
+        data.truncate(len - 1);
+        let mut reader = AcsReader::new(&data);
+        let header = reader.read_header().unwrap();
+        assert!(matches!(
+            reader.read_audio_list(&header.audio_info),
+            Err(ReaderError::InvalidOffset { .. })
+        ));
+    }
 }
@@ -0,0 +1,572 @@
+//! Rendering options and post-processing transforms for decoded images.
+
+use crate::acs::{AcsError, Image, OverlayType};
+
+/// Order in which a frame's images are drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageOrder {
+    /// Draw images in the order they are stored, so the last image ends up on top.
+    AsStored,
+    /// Draw images last to first, so the first stored image ends up on top.
+    ///
+    /// This matches Agent, which lists a frame's images from front to back.
+    #[default]
+    Reversed,
+}
+
+/// Options controlling how frames are rendered.
+///
+/// The default produces the same output as `Acs::render_frame`.
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Output premultiplied alpha instead of straight alpha.
+    pub premultiply: bool,
+    /// Replacement RGBA palette for recoloring, used in place of the character palette.
+    ///
+    /// Must cover every index of the character palette (and at most 256 entries). The
+    /// transparent index is still rendered fully transparent.
+    pub palette_override: Option<Vec<[u8; 4]>>,
+    /// Z-order of multi-image frames. Only worth changing if a character renders with
+    /// parts hidden behind each other.
+    pub image_order: ImageOrder,
+    /// Outline each placed frame image and mark the canvas center in
+    /// [`DEBUG_OVERLAY_COLOR`]. A placement aid for animators; leave off for real output.
+    pub debug_overlay: bool,
+    /// Draw the frame's mouth overlay of this type on top, for lip-synced speech. Frames
+    /// without a matching overlay render as usual.
+    pub mouth: Option<OverlayType>,
+    /// Composite the frame over this straight-alpha RGBA color instead of leaving
+    /// transparent pixels transparent.
+    pub background: Option<[u8; 4]>,
+    /// Integer nearest-neighbour upscale applied last (see [`Image::scale_nearest`]);
+    /// 0 and 1 both mean no scaling.
+    pub scale: u32,
+    /// Multiply RGB by this factor (see [`Image::adjust_brightness`]); `None` leaves
+    /// colors as decoded.
+    pub brightness: Option<f32>,
+}
+
+/// Color of the outlines and crosshair drawn by `RenderOptions::debug_overlay`.
+pub const DEBUG_OVERLAY_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+/// Half-length of the center crosshair arms, in pixels.
+const CROSSHAIR_RADIUS: i64 = 4;
+
+impl Image {
+    fn put_debug_pixel(&mut self, x: i64, y: i64) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let i = ((y * self.width as i64 + x) * 4) as usize;
+        self.data[i..i + 4].copy_from_slice(&DEBUG_OVERLAY_COLOR);
+    }
+
+    /// Draw a 1px outline around each `(x, y, width, height)` rect and a crosshair at the
+    /// center of the image, clipped to its bounds.
+    pub(crate) fn draw_debug_overlay(&mut self, rects: &[(i64, i64, u32, u32)]) {
+        for &(x, y, width, height) in rects {
+            if width == 0 || height == 0 {
+                continue;
+            }
+            let (right, bottom) = (x + width as i64 - 1, y + height as i64 - 1);
+            for px in x..=right {
+                self.put_debug_pixel(px, y);
+                self.put_debug_pixel(px, bottom);
+            }
+            for py in y..=bottom {
+                self.put_debug_pixel(x, py);
+                self.put_debug_pixel(right, py);
+            }
+        }
+
+        let (cx, cy) = (self.width as i64 / 2, self.height as i64 / 2);
+        for d in -CROSSHAIR_RADIUS..=CROSSHAIR_RADIUS {
+            self.put_debug_pixel(cx + d, cy);
+            self.put_debug_pixel(cx, cy + d);
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Apply the post-compositing transforms to a rendered image.
+    pub(crate) fn apply(&self, image: Image) -> Result<Image, AcsError> {
+        let image = match self.brightness {
+            Some(factor) => image.adjust_brightness(factor),
+            None => image,
+        };
+        let image = match self.background {
+            Some(color) => image.over_background(color),
+            None => image,
+        };
+        let image = if self.premultiply {
+            image.premultiply()
+        } else {
+            image
+        };
+        if self.scale > 1 {
+            image.scale_nearest(self.scale)
+        } else {
+            Ok(image)
+        }
+    }
+}
+
+impl Image {
+    /// Copy the opaque pixels of `src` onto this image with its top-left at `(left, top)`.
+    pub(crate) fn blit(&mut self, src: &Image, left: i64, top: i64) {
+        // Clip the source rect against the canvas once, so images placed partly (or
+        // entirely) off-canvas only copy the overlapping region
+        let x0 = left.max(0);
+        let y0 = top.max(0);
+        let x1 = (left + src.width as i64).min(self.width as i64);
+        let y1 = (top + src.height as i64).min(self.height as i64);
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+
+        for dst_y in y0..y1 {
+            let src_row = ((dst_y - top) * src.width as i64) as usize;
+            let dst_row = (dst_y * self.width as i64) as usize;
+            for dst_x in x0..x1 {
+                let src_idx = (src_row + (dst_x - left) as usize) * 4;
+                let dst_idx = (dst_row + dst_x as usize) * 4;
+
+                if src.data[src_idx + 3] > 0 {
+                    self.data[dst_idx..dst_idx + 4]
+                        .copy_from_slice(&src.data[src_idx..src_idx + 4]);
+                }
+            }
+        }
+    }
+
+    /// Composite over a solid straight-alpha RGBA `color` (the "over" operator).
+    pub fn over_background(&self, color: [u8; 4]) -> Image {
+        let bg_alpha = color[3] as u32;
+        let mut data = self.data.clone();
+        for px in data.chunks_exact_mut(4) {
+            let alpha = px[3] as u32;
+            // Background contribution, in units of 1/255^2
+            let under = bg_alpha * (255 - alpha);
+            let out_alpha = alpha * 255 + under;
+            if out_alpha == 0 {
+                continue;
+            }
+            for (c, &bg) in px[..3].iter_mut().zip(&color[..3]) {
+                let sum = *c as u32 * alpha * 255 + bg as u32 * under;
+                *c = ((sum + out_alpha / 2) / out_alpha) as u8;
+            }
+            px[3] = ((out_alpha + 127) / 255) as u8;
+        }
+        Image {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+
+    /// Map every RGB channel through a 256-entry lookup table, leaving alpha alone.
+    fn map_channels(&self, f: impl Fn(f32) -> f32) -> Image {
+        let lut: Vec<u8> = (0..=255)
+            .map(|c| f(c as f32).round().clamp(0.0, 255.0) as u8)
+            .collect();
+        let mut data = self.data.clone();
+        for px in data.chunks_exact_mut(4) {
+            for c in &mut px[..3] {
+                *c = lut[*c as usize];
+            }
+        }
+        Image {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+
+    /// Scale each RGB channel by `factor`, clamping to 255. Alpha is preserved.
+    ///
+    /// 1.0 is the identity; above brightens, below darkens. Handy for characters drawn for
+    /// old CRTs that look too dark on modern displays.
+    pub fn adjust_brightness(&self, factor: f32) -> Image {
+        self.map_channels(|c| c * factor)
+    }
+
+    /// Apply a gamma curve to each RGB channel: `255 * (c / 255) ^ (1 / gamma)`.
+    ///
+    /// 1.0 is the identity; above lifts midtones while keeping black and white fixed.
+    /// Alpha is preserved. Non-positive values leave the image unchanged.
+    pub fn adjust_gamma(&self, gamma: f32) -> Image {
+        if gamma <= 0.0 {
+            return self.clone();
+        }
+        self.map_channels(|c| 255.0 * (c / 255.0).powf(1.0 / gamma))
+    }
+
+    /// Convert straight alpha to premultiplied alpha.
+    ///
+    /// Each RGB channel is scaled by `alpha / 255`, as expected by some GPU upload paths
+    /// and canvas composite modes.
+    pub fn premultiply(&self) -> Image {
+        let mut data = self.data.clone();
+        for px in data.chunks_exact_mut(4) {
+            let alpha = px[3] as u16;
+            for c in &mut px[..3] {
+                *c = ((*c as u16 * alpha + 127) / 255) as u8;
+            }
+        }
+
+        Image {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+
+    /// Upscale by an integer `factor` with nearest-neighbour sampling, keeping pixel art crisp.
+    ///
+    /// A factor of 0 is treated as 1. Fails if the scaled dimensions or buffer size
+    /// don't fit in memory.
+    pub fn scale_nearest(&self, factor: u32) -> Result<Image, AcsError> {
+        let factor = factor.max(1);
+        let overflow = AcsError::ScaleOverflow {
+            width: self.width,
+            height: self.height,
+            factor,
+        };
+        let (Some(width), Some(height)) = (
+            self.width.checked_mul(factor),
+            self.height.checked_mul(factor),
+        ) else {
+            return Err(overflow);
+        };
+        let Some(len) = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|n| n.checked_mul(4))
+        else {
+            return Err(overflow);
+        };
+        let mut data = Vec::with_capacity(len);
+
+        for y in 0..height {
+            let row = (y / factor) as usize * self.width as usize;
+            for x in 0..width {
+                let i = (row + (x / factor) as usize) * 4;
+                data.extend_from_slice(&self.data[i..i + 4]);
+            }
+        }
+
+        Ok(Image {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Shrink to fit within `max_w` x `max_h`, keeping the aspect ratio, for thumbnails.
+    ///
+    /// Each output pixel is the box-filtered average of the source pixels it covers, with
+    /// RGB weighted by alpha so transparent pixels don't darken the edges. Returns a copy
+    /// of the image unchanged if it already fits.
+    pub fn downscale(&self, max_w: u32, max_h: u32) -> Image {
+        let (max_w, max_h) = (max_w.max(1), max_h.max(1));
+        if self.width <= max_w && self.height <= max_h {
+            return self.clone();
+        }
+
+        let (src_w, src_h) = (self.width as u64, self.height as u64);
+        let (width, height) = if src_w * max_h as u64 >= src_h * max_w as u64 {
+            (max_w, ((src_h * max_w as u64) / src_w).max(1) as u32)
+        } else {
+            (((src_w * max_h as u64) / src_h).max(1) as u32, max_h)
+        };
+
+        // Source span [start, end) covered by output index `i`, at least one pixel wide
+        let span = |i: u32, src: u64, dst: u32| {
+            let start = i as u64 * src / dst as u64;
+            let end = ((i as u64 + 1) * src / dst as u64).max(start + 1);
+            start as usize..end as usize
+        };
+
+        let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            let rows = span(y, src_h, height);
+            for x in 0..width {
+                let cols = span(x, src_w, width);
+                let mut rgb = [0u64; 3];
+                let mut alpha = 0u64;
+                for sy in rows.clone() {
+                    for sx in cols.clone() {
+                        let i = (sy * self.width as usize + sx) * 4;
+                        let a = self.data[i + 3] as u64;
+                        for (sum, &c) in rgb.iter_mut().zip(&self.data[i..i + 3]) {
+                            *sum += c as u64 * a;
+                        }
+                        alpha += a;
+                    }
+                }
+
+                let count = (rows.len() * cols.len()) as u64;
+                if alpha == 0 {
+                    data.extend_from_slice(&[0, 0, 0, 0]);
+                } else {
+                    data.extend(rgb.map(|sum| ((sum + alpha / 2) / alpha) as u8));
+                    data.push(((alpha + count / 2) / count) as u8);
+                }
+            }
+        }
+
+        Image {
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+/// Find the palette entry closest to `color` by Euclidean RGB distance.
+///
+/// Alpha is ignored. Ties go to the lowest index; returns `None` for an empty palette.
+pub fn nearest_index(color: [u8; 4], palette: &[[u8; 4]]) -> Option<usize> {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, entry)| {
+            (0..3)
+                .map(|c| {
+                    let d = color[c] as i32 - entry[c] as i32;
+                    (d * d) as u32
+                })
+                .sum::<u32>()
+        })
+        .map(|(i, _)| i)
+}
+
+impl Image {
+    /// Snap every pixel to its nearest color in `target`, keeping the original alpha.
+    ///
+    /// Useful for theming a character to a UI palette or posterizing it. Fully
+    /// transparent pixels are left untouched, as is everything if `target` is empty.
+    pub fn remap_palette(&self, target: &[[u8; 4]]) -> Image {
+        let mut data = self.data.clone();
+        for px in data.chunks_exact_mut(4) {
+            if px[3] == 0 {
+                continue;
+            }
+            if let Some(i) = nearest_index([px[0], px[1], px[2], px[3]], target) {
+                px[..3].copy_from_slice(&target[i][..3]);
+            }
+        }
+
+        Image {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+
+    /// Cross-fade towards `other` by `amount` (0.0 = self, 1.0 = other).
+    ///
+    /// Colors are mixed with premultiplied weights so fully transparent pixels don't
+    /// darken the result. Both images must have the same dimensions.
+    pub(crate) fn blend(&self, other: &Image, amount: f32) -> Image {
+        let t = amount.clamp(0.0, 1.0);
+        let mut data = Vec::with_capacity(self.data.len());
+
+        for (a, b) in self.data.chunks_exact(4).zip(other.data.chunks_exact(4)) {
+            let alpha_a = a[3] as f32 / 255.0;
+            let alpha_b = b[3] as f32 / 255.0;
+            let alpha = alpha_a + (alpha_b - alpha_a) * t;
+            if alpha <= 0.0 {
+                data.extend_from_slice(&[0, 0, 0, 0]);
+                continue;
+            }
+            for c in 0..3 {
+                let mixed = (a[c] as f32 * alpha_a * (1.0 - t) + b[c] as f32 * alpha_b * t) / alpha;
+                data.push(mixed.round().clamp(0.0, 255.0) as u8);
+            }
+            data.push((alpha * 255.0).round() as u8);
+        }
+
+        Image {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_premultiply() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            data: vec![255, 255, 255, 127, 10, 20, 30, 255],
+        };
+        let premultiplied = image.premultiply();
+        assert_eq!(&premultiplied.data[..4], &[127, 127, 127, 127]);
+        assert_eq!(&premultiplied.data[4..], &[10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn test_scale_nearest() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            data: vec![1, 2, 3, 4, 5, 6, 7, 8],
+        };
+        let scaled = image.scale_nearest(2).unwrap();
+        assert_eq!((scaled.width, scaled.height), (4, 2));
+        let row = [1, 2, 3, 4, 1, 2, 3, 4, 5, 6, 7, 8, 5, 6, 7, 8];
+        assert_eq!(&scaled.data[..16], &row);
+        assert_eq!(&scaled.data[16..], &row);
+        assert_eq!(image.scale_nearest(0).unwrap().data, image.data);
+        assert!(matches!(
+            image.scale_nearest(u32::MAX),
+            Err(AcsError::ScaleOverflow {
+                factor: u32::MAX,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_downscale() {
+        // Opaque red up to column 98, transparent black after
+        let (width, height) = (200, 160);
+        let data = (0..width * height)
+            .flat_map(|i| {
+                if i % width < 98 {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 0, 0]
+                }
+            })
+            .collect();
+        let image = Image {
+            width,
+            height,
+            data,
+        };
+
+        let thumb = image.downscale(64, 64);
+        assert_eq!((thumb.width, thumb.height), (64, 51));
+        assert_eq!(thumb.data.len(), 64 * 51 * 4);
+        assert_eq!(&thumb.data[..4], &[255, 0, 0, 255]);
+
+        // The column straddling the edge is half covered, but stays fully red
+        let edge = &thumb.data[31 * 4..32 * 4];
+        assert_eq!(&edge[..3], &[255, 0, 0]);
+        assert!(edge[3] > 0 && edge[3] < 255);
+        assert_eq!(&thumb.data[63 * 4..64 * 4], &[0, 0, 0, 0]);
+
+        let small = image.downscale(400, 400);
+        assert_eq!((small.width, small.height), (200, 160));
+        assert_eq!(small.data, image.data);
+    }
+
+    #[test]
+    fn test_adjust_brightness_and_gamma() {
+        let image = Image {
+            width: 3,
+            height: 1,
+            data: vec![0, 64, 128, 255, 200, 255, 1, 128, 10, 20, 30, 0],
+        };
+
+        assert_eq!(image.adjust_brightness(1.0).data, image.data);
+        assert_eq!(image.adjust_gamma(1.0).data, image.data);
+
+        let bright = image.adjust_brightness(2.0);
+        assert_eq!(
+            bright.data,
+            [0, 128, 255, 255, 255, 255, 2, 128, 20, 40, 60, 0]
+        );
+
+        let gamma = image.adjust_gamma(2.2);
+        assert_eq!((gamma.data[0], gamma.data[4], gamma.data[5]), (0, 228, 255));
+        assert!(gamma.data[1] > 64 && gamma.data[2] > 128);
+        assert_eq!(gamma.data[7], 128);
+    }
+
+    #[test]
+    fn test_over_background() {
+        let image = Image {
+            width: 3,
+            height: 1,
+            data: vec![255, 0, 0, 255, 0, 0, 0, 0, 255, 255, 255, 128],
+        };
+        let flat = image.over_background([0, 0, 255, 255]);
+        assert_eq!(
+            flat.data,
+            [255, 0, 0, 255, 0, 0, 255, 255, 128, 128, 255, 255]
+        );
+        assert_eq!(image.over_background([0, 0, 0, 0]).data, image.data);
+    }
+
+    #[test]
+    fn test_remap_palette() {
+        let image = Image {
+            width: 3,
+            height: 1,
+            data: vec![250, 10, 10, 255, 20, 20, 200, 128, 250, 10, 10, 0],
+        };
+        let target = [[0, 0, 255, 255], [255, 0, 0, 255], [0, 0, 0, 255]];
+
+        assert_eq!(nearest_index([250, 10, 10, 255], &target), Some(1));
+        assert_eq!(nearest_index([0, 0, 0, 0], &[]), None);
+
+        let remapped = image.remap_palette(&target);
+        assert_eq!(&remapped.data[..4], &[255, 0, 0, 255]);
+        assert_eq!(&remapped.data[4..8], &[0, 0, 255, 128]);
+        // Transparent pixels are left alone
+        assert_eq!(&remapped.data[8..], &[250, 10, 10, 0]);
+    }
+
+    #[test]
+    fn test_draw_debug_overlay() {
+        let mut image = Image {
+            width: 9,
+            height: 9,
+            data: vec![0; 9 * 9 * 4],
+        };
+        // Partly off-canvas rects are clipped rather than panicking
+        image.draw_debug_overlay(&[(1, 1, 3, 2), (-2, 7, 5, 5)]);
+
+        let px = |x: usize, y: usize| &image.data[(y * 9 + x) * 4..(y * 9 + x) * 4 + 4];
+        for (x, y) in [
+            (1, 1),
+            (3, 1),
+            (1, 2),
+            (3, 2),
+            (0, 7),
+            (2, 8),
+            (4, 4),
+            (8, 4),
+        ] {
+            assert_eq!(px(x, y), DEBUG_OVERLAY_COLOR, "({}, {})", x, y);
+        }
+        assert_eq!(px(2, 3), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_blend() {
+        let a = Image {
+            width: 2,
+            height: 1,
+            data: vec![200, 0, 0, 255, 0, 0, 0, 0],
+        };
+        let b = Image {
+            width: 2,
+            height: 1,
+            data: vec![0, 0, 200, 255, 0, 100, 0, 255],
+        };
+        assert_eq!(a.blend(&b, 0.0).data, a.data);
+        assert_eq!(a.blend(&b, 1.0).data, b.data);
+        let half = a.blend(&b, 0.5);
+        assert_eq!(&half.data[..4], &[100, 0, 100, 255]);
+        // Fading in from transparent keeps the incoming color rather than darkening it
+        assert_eq!(&half.data[4..], &[0, 100, 0, 128]);
+    }
+}
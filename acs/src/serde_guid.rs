@@ -0,0 +1,26 @@
+//! Serialize 16-byte GUIDs as 32-character hex strings rather than byte arrays.
+//!
+//! Bytes are written in file order, so the string is not the registry form produced by
+//! `CharacterInfo::guid_string`, but round-trips exactly.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(guid: &[u8; 16], serializer: S) -> Result<S::Ok, S::Error> {
+    let hex: String = guid.iter().map(|b| format!("{:02x}", b)).collect();
+    serializer.serialize_str(&hex)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 16], D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    if hex.len() != 32 || !hex.is_ascii() {
+        return Err(D::Error::custom("GUID must be 32 hex digits"));
+    }
+
+    let mut guid = [0u8; 16];
+    for (i, byte) in guid.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| D::Error::custom("GUID must be 32 hex digits"))?;
+    }
+    Ok(guid)
+}
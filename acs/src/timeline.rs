@@ -0,0 +1,109 @@
+//! Precomputed frame start times for fast time/frame lookups.
+
+use crate::acs::Animation;
+
+/// Cumulative frame start times for one animation.
+///
+/// Built by [`Animation::build_timeline`], or cached per animation by
+/// `Acs::animation_timeline`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timeline {
+    /// Start time of each frame in milliseconds
+    starts: Vec<u32>,
+    total_ms: u32,
+}
+
+impl Timeline {
+    /// Index of the frame showing at `t_ms`, found by binary search.
+    ///
+    /// Times past the end give the last frame; an empty timeline always gives 0.
+    /// Zero-duration frames are never returned unless they're the last frame.
+    pub fn frame_at(&self, t_ms: u32) -> usize {
+        self.starts
+            .partition_point(|&start| start <= t_ms)
+            .saturating_sub(1)
+    }
+
+    /// Start time of `frame` in milliseconds. Indices past the end give the total duration.
+    pub fn start_of(&self, frame: usize) -> u32 {
+        self.starts.get(frame).copied().unwrap_or(self.total_ms)
+    }
+
+    /// Duration of `frame` in milliseconds, or 0 past the end.
+    pub fn duration_of(&self, frame: usize) -> u32 {
+        self.start_of(frame + 1) - self.start_of(frame)
+    }
+
+    /// Total animation duration in milliseconds.
+    pub fn total_ms(&self) -> u32 {
+        self.total_ms
+    }
+
+    /// Number of frames.
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+}
+
+impl Animation {
+    /// Precompute frame start times so time/frame conversions are O(log n).
+    pub fn build_timeline(&self) -> Timeline {
+        let mut starts = Vec::with_capacity(self.frames.len());
+        let mut total_ms: u32 = 0;
+        for frame in &self.frames {
+            starts.push(total_ms);
+            // Saturate rather than wrap so start times stay sorted for the binary search
+            total_ms = total_ms.saturating_add(frame.duration_ms);
+        }
+        Timeline { starts, total_ms }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_lookup() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let mut acs = crate::Acs::new(std::fs::read(path).unwrap()).unwrap();
+        let animation = acs.animation("Greet").unwrap();
+        let timeline = animation.build_timeline();
+
+        assert_eq!(timeline.len(), animation.frames.len());
+        assert!(timeline.len() > 1);
+        assert_eq!(timeline.total_ms(), animation.total_duration_ms());
+        for i in 0..timeline.len() {
+            assert_eq!(timeline.frame_at(timeline.start_of(i)), i);
+            assert_eq!(timeline.duration_of(i), animation.frames[i].duration_ms);
+        }
+        assert_eq!(timeline.frame_at(timeline.start_of(1) - 1), 0);
+        assert_eq!(timeline.frame_at(u32::MAX), timeline.len() - 1);
+        assert_eq!(timeline.start_of(timeline.len()), timeline.total_ms());
+
+        let empty = Timeline {
+            starts: Vec::new(),
+            total_ms: 0,
+        };
+        assert_eq!(empty.frame_at(100), 0);
+    }
+
+    #[test]
+    fn test_timeline_saturates() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let mut acs = crate::Acs::new(std::fs::read(path).unwrap()).unwrap();
+        let mut animation = acs.animation("Greet").unwrap().clone();
+        for frame in &mut animation.frames {
+            frame.duration_ms = u32::MAX / 2;
+        }
+
+        let timeline = animation.build_timeline();
+        assert_eq!(timeline.total_ms(), u32::MAX);
+        assert_eq!(timeline.start_of(timeline.len() - 1), u32::MAX);
+        assert_eq!(timeline.frame_at(u32::MAX / 2), 1);
+    }
+}
@@ -0,0 +1,280 @@
+//! Lossy compression of embedded sounds, behind the `audio-transcode` feature.
+//!
+//! Sounds are encoded as Opus in an Ogg container, which every major browser plays. The
+//! feature adds two dependencies:
+//!
+//! - `audiopus`, bindings to the C library libopus. Its build script links a system
+//!   libopus found through `pkg-config`, or builds the bundled source with CMake, so
+//!   building needs a C compiler and either the library or CMake. It doesn't build for
+//!   `wasm32-unknown-unknown`, so transcode ahead of time rather than in the browser.
+//! - `ogg`, a pure-Rust Ogg page writer (which pulls in `byteorder`).
+
+use audiopus::coder::Encoder;
+use audiopus::{Application, Channels, SampleRate};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+use crate::acs::{Acs, AcsError};
+use crate::audio::AudioFormat;
+use crate::wav::{self, WavError};
+
+/// Output formats for `Acs::sound_transcoded`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TranscodeFormat {
+    /// Opus in an Ogg container (`.opus`, `audio/ogg; codecs=opus`)
+    OggOpus,
+}
+
+/// Length of each Opus packet; 20 ms is libopus's recommended default.
+const FRAME_MS: u32 = 20;
+
+/// Ogg Opus counts granule positions and the pre-skip in 48 kHz samples whatever the
+/// encoder's rate.
+const GRANULE_HZ: u32 = 48_000;
+
+/// Output buffer size libopus recommends for a single packet.
+const MAX_PACKET_BYTES: usize = 4000;
+
+/// Serial number of the one logical stream in each file.
+const STREAM_SERIAL: u32 = 1;
+
+impl Acs {
+    /// Compress a sound for delivery, e.g. to cut the download size of a web character.
+    ///
+    /// Only mono and stereo 8- or 16-bit PCM sounds can be transcoded. Opus runs at 8,
+    /// 12, 16, 24 or 48 kHz, so other rates are resampled up to the next of those
+    /// (11,025 Hz to 12 kHz, 22,050 Hz to 24 kHz); the stream header keeps the original
+    /// rate.
+    pub fn sound_transcoded(
+        &self,
+        index: usize,
+        format: TranscodeFormat,
+    ) -> Result<Vec<u8>, AcsError> {
+        let sound = self.sound(index)?;
+        let (wav_format, data) = sound.sample_data()?;
+        match format {
+            TranscodeFormat::OggOpus => encode_ogg_opus(wav_format, data),
+        }
+    }
+}
+
+/// Encode interleaved PCM (unsigned 8-bit or little-endian signed 16-bit) as Ogg Opus.
+fn encode_ogg_opus(format: AudioFormat, data: &[u8]) -> Result<Vec<u8>, AcsError> {
+    let opus_channels = match format.channels {
+        1 => Channels::Mono,
+        2 => Channels::Stereo,
+        _ => return Err(WavError::Unsupported("Opus needs mono or stereo").into()),
+    };
+    let samples: Vec<i16> = match format.bits_per_sample {
+        // 8-bit PCM is unsigned
+        8 => data.iter().map(|&b| (b as i16 - 128) << 8).collect(),
+        16 => data
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect(),
+        _ => return Err(WavError::Unsupported("Opus encoding needs 8- or 16-bit PCM").into()),
+    };
+    if format.sample_rate == 0 {
+        return Err(WavError::Malformed("zero sample rate").into());
+    }
+
+    let rate = [
+        SampleRate::Hz8000,
+        SampleRate::Hz12000,
+        SampleRate::Hz16000,
+        SampleRate::Hz24000,
+    ]
+    .into_iter()
+    .find(|&rate| rate as u32 >= format.sample_rate)
+    .unwrap_or(SampleRate::Hz48000);
+    let opus_hz = rate as u32;
+    let channels = format.channels as usize;
+    let mut samples = if opus_hz == format.sample_rate {
+        samples
+    } else {
+        wav::resample(&samples, channels, format.sample_rate, opus_hz)
+    };
+    let frames = samples.len() / channels;
+
+    let encoder = Encoder::new(rate, opus_channels, Application::Audio)?;
+    let lookahead = encoder.lookahead()?;
+    let scale = (GRANULE_HZ / opus_hz) as u64;
+    let pre_skip = lookahead as u64 * scale;
+    // The encoder holds back `lookahead` samples, so feed it that much silence to flush
+    // out the end of the sound
+    samples.resize((frames + lookahead as usize) * channels, 0);
+
+    let mut writer = PacketWriter::new(Vec::new());
+    let header_page = PacketWriteEndInfo::EndPage;
+    writer.write_packet(
+        opus_head(format, pre_skip as u16),
+        STREAM_SERIAL,
+        header_page,
+        0,
+    )?;
+    writer.write_packet(opus_tags(), STREAM_SERIAL, header_page, 0)?;
+
+    let packet_frames = (opus_hz * FRAME_MS / 1000) as usize;
+    let packet_count = (frames + lookahead as usize).div_ceil(packet_frames);
+    let mut input = vec![0i16; packet_frames * channels];
+    let mut packet = [0u8; MAX_PACKET_BYTES];
+    for (i, chunk) in samples.chunks(input.len()).enumerate() {
+        // The last packet is padded with silence to a whole frame
+        input[..chunk.len()].copy_from_slice(chunk);
+        input[chunk.len()..].fill(0);
+        let len = encoder.encode(&input, &mut packet)?;
+
+        let (end, granule) = if i + 1 == packet_count {
+            // The final granule position trims the padding off the decoded audio
+            (
+                PacketWriteEndInfo::EndStream,
+                pre_skip + frames as u64 * scale,
+            )
+        } else {
+            let decoded = ((i + 1) * packet_frames) as u64 * scale;
+            (PacketWriteEndInfo::NormalPacket, decoded)
+        };
+        writer.write_packet(packet[..len].into(), STREAM_SERIAL, end, granule)?;
+    }
+    Ok(writer.into_inner())
+}
+
+/// The `OpusHead` identification header (RFC 7845, section 5.1).
+fn opus_head(format: AudioFormat, pre_skip: u16) -> Box<[u8]> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // version
+    head.push(format.channels as u8);
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&format.sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // mapping family 0: mono or stereo, no channel mapping table
+    head.into_boxed_slice()
+}
+
+/// The `OpusTags` comment header (RFC 7845, section 5.2), naming the encoder and no
+/// comments.
+fn opus_tags() -> Box<[u8]> {
+    let vendor = audiopus::version();
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags.into_boxed_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use audiopus::coder::Decoder;
+    use ogg::reading::PacketReader;
+
+    use super::*;
+
+    struct Decoded {
+        channels: usize,
+        pre_skip: u64,
+        /// Granule position of the last page: pre-skip plus playable samples
+        end_granule: u64,
+        samples: Vec<i16>,
+    }
+
+    fn decode(opus: &[u8]) -> Decoded {
+        let mut reader = PacketReader::new(Cursor::new(opus));
+        let head = reader.read_packet_expected().unwrap().data;
+        assert_eq!(&head[..8], b"OpusHead");
+        let tags = reader.read_packet_expected().unwrap().data;
+        assert_eq!(&tags[..8], b"OpusTags");
+
+        let channels = head[9] as usize;
+        let pre_skip = u16::from_le_bytes([head[10], head[11]]) as u64;
+        let opus_channels = if channels == 1 {
+            Channels::Mono
+        } else {
+            Channels::Stereo
+        };
+        let mut decoder = Decoder::new(SampleRate::Hz48000, opus_channels).unwrap();
+
+        // 120 ms at 48 kHz, the longest an Opus packet can be
+        let mut buffer = vec![0i16; 5760 * channels];
+        let mut samples = Vec::new();
+        let mut end_granule = 0;
+        while let Some(packet) = reader.read_packet().unwrap() {
+            let input = packet.data.as_slice().try_into().unwrap();
+            let output = buffer.as_mut_slice().try_into().unwrap();
+            let frames = decoder.decode(Some(input), output, false).unwrap();
+            samples.extend_from_slice(&buffer[..frames * channels]);
+            end_granule = packet.absgp_page();
+            if packet.last_in_stream() {
+                break;
+            }
+        }
+        Decoded {
+            channels,
+            pre_skip,
+            end_granule,
+            samples,
+        }
+    }
+
+    #[test]
+    fn test_ogg_opus_round_trip() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let acs = Acs::new(std::fs::read(path).unwrap()).unwrap();
+        let longest = (0..acs.sound_count())
+            .max_by_key(|&i| acs.sound(i).unwrap().data.len())
+            .unwrap();
+
+        for index in 0..acs.sound_count() {
+            let sound = acs.sound(index).unwrap();
+            let (format, data) = sound.sample_data().unwrap();
+            let frames = data.len() / (format.channels * format.bits_per_sample / 8) as usize;
+
+            let opus = acs
+                .sound_transcoded(index, TranscodeFormat::OggOpus)
+                .unwrap();
+            let decoded = decode(&opus);
+            assert_eq!(decoded.channels, format.channels as usize);
+
+            // Playable length at 48 kHz, within a sample or two of resampling rounding
+            let playable = decoded.end_granule - decoded.pre_skip;
+            let expected = frames as u64 * 48_000 / format.sample_rate as u64;
+            assert!(
+                playable.abs_diff(expected) <= 4,
+                "sound {}: {} samples, expected {}",
+                index,
+                playable,
+                expected
+            );
+            let decoded_frames = (decoded.samples.len() / decoded.channels) as u64;
+            assert!(decoded_frames >= decoded.end_granule, "sound {}", index);
+
+            if index == longest {
+                assert!(opus.len() < sound.data.len() / 4, "sound {}", index);
+                assert!(decoded.samples.iter().any(|&s| s.unsigned_abs() > 1000));
+            }
+        }
+    }
+
+    #[test]
+    fn test_ogg_opus_rejects_unsupported_formats() {
+        let format = AudioFormat {
+            sample_rate: 11025,
+            channels: 3,
+            bits_per_sample: 16,
+        };
+        assert!(matches!(
+            encode_ogg_opus(format, &[0; 12]),
+            Err(AcsError::InvalidWav(WavError::Unsupported(_)))
+        ));
+        let format = AudioFormat {
+            channels: 1,
+            bits_per_sample: 24,
+            ..format
+        };
+        assert!(matches!(
+            encode_ogg_opus(format, &[0; 12]),
+            Err(AcsError::InvalidWav(WavError::Unsupported(_)))
+        ));
+    }
+}
@@ -0,0 +1,224 @@
+//! Structural checks over a whole character file.
+
+use std::fmt;
+
+use crate::acs::{Acs, AcsError};
+
+/// How serious a validation finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Likely harmless, but not what Agent itself would produce.
+    Warning = 1,
+    /// Something a player will trip over, such as an out-of-range index.
+    Error = 2,
+}
+
+/// A single problem found by [`Acs::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn warning(message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+
+    fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}", level, self.message)
+    }
+}
+
+impl Acs {
+    /// Run every structural check and report what was found.
+    ///
+    /// Checks the palette and transparent index, every animation's image, overlay, sound,
+    /// branch and return references, and that each sound is a readable WAV file. Exit
+    /// branches dropped on load are reported as warnings. This loads (and caches) every
+    /// animation. An empty result means the file is consistent.
+    pub fn validate(&mut self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        let info = self.character_info();
+        if info.palette.is_empty() || info.palette.len() > 256 {
+            issues.push(ValidationIssue::error(format!(
+                "palette has {} entries",
+                info.palette.len()
+            )));
+        }
+        if info.transparent_color as usize >= info.palette.len() {
+            issues.push(ValidationIssue::warning(format!(
+                "transparent index {} is outside the {}-entry palette",
+                info.transparent_color,
+                info.palette.len()
+            )));
+        }
+
+        for index in 0..self.sound_count() {
+            if let Err(e) = self.sound(index).and_then(|s| s.format()) {
+                issues.push(ValidationIssue::error(format!("sound {}: {}", index, e)));
+            }
+        }
+
+        let image_count = self.image_count();
+        let sound_count = self.sound_count();
+        let names: Vec<String> = self
+            .animation_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let exists = |name: &str| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+
+        let mut animation_issues = Vec::new();
+        let failures = self.try_each_animation(|name, anim| {
+            let mut error = |message: String| {
+                animation_issues.push(ValidationIssue::error(format!("{}: {}", name, message)));
+            };
+
+            for (i, frame) in anim.frames.iter().enumerate() {
+                for image in &frame.images {
+                    if image.image_index >= image_count {
+                        error(format!(
+                            "frame {} uses missing image {}",
+                            i, image.image_index
+                        ));
+                    }
+                }
+                for overlay in &frame.overlays {
+                    if overlay.image_index >= image_count {
+                        error(format!(
+                            "frame {} overlay uses missing image {}",
+                            i, overlay.image_index
+                        ));
+                    }
+                }
+                if let Some(sound) = frame.sound_index
+                    && sound >= sound_count
+                {
+                    error(format!("frame {} uses missing sound {}", i, sound));
+                }
+                for branch in &frame.branches {
+                    if branch.frame_index >= anim.frames.len() {
+                        error(format!(
+                            "frame {} branches to missing frame {}",
+                            i, branch.frame_index
+                        ));
+                    }
+                }
+            }
+
+            if let Some(ref ret) = anim.return_animation
+                && !exists(ret)
+            {
+                animation_issues.push(ValidationIssue::warning(format!(
+                    "{}: return animation {} does not exist",
+                    name, ret
+                )));
+            }
+        });
+
+        for (name, e) in failures {
+            issues.push(ValidationIssue::error(format!("{}: {}", name, e)));
+        }
+        issues.extend(animation_issues);
+        for warning in self.load_warnings() {
+            if let AcsError::InvalidExitBranch { .. } = warning {
+                issues.push(ValidationIssue::warning(warning.to_string()));
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_bonzi() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let mut acs = Acs::new(std::fs::read(path).unwrap()).unwrap();
+        let issues = acs.validate();
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_validate_reports_bad_sound() {
+        use crate::reader::AcsReader;
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+        let mut data = std::fs::read(path).unwrap();
+        let mut reader = AcsReader::new(&data);
+        let header = reader.read_header().unwrap();
+        let offset = reader.read_audio_list(&header.audio_info).unwrap()[0]
+            .locator
+            .offset as usize;
+        data[offset..offset + 4].copy_from_slice(b"JUNK");
+
+        let mut acs = Acs::new(data).unwrap();
+        let issues = acs.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert!(issues[0].message.starts_with("sound 0"));
+    }
+
+    #[test]
+    fn test_out_of_range_exit_branch_dropped() {
+        use crate::acs::{Animation, Frame, TransitionType};
+        use crate::writer::AcsBuilder;
+
+        let frame = |exit_branch| Frame {
+            images: Vec::new(),
+            duration_ms: 100,
+            sound_index: None,
+            exit_branch,
+            branches: Vec::new(),
+            overlays: Vec::new(),
+        };
+        let data = AcsBuilder::new(1, 1)
+            .palette(vec![[0, 0, 0]])
+            .animation(Animation {
+                name: "Wave".to_string(),
+                frames: vec![frame(Some(2)), frame(Some(5))],
+                return_animation: None,
+                transition_type: TransitionType::UseExitBranch,
+            })
+            .build();
+
+        let mut acs = Acs::new(data).unwrap();
+        let frames = &acs.animation("Wave").unwrap().frames;
+        // One past the end is kept, as it just ends the animation
+        assert_eq!(frames[0].exit_branch, Some(2));
+        assert_eq!(frames[1].exit_branch, None);
+        assert!(matches!(
+            acs.load_warnings(),
+            [AcsError::InvalidExitBranch {
+                frame: 1,
+                target: 5,
+                ..
+            }]
+        ));
+
+        let issues = acs.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(issues[0].message, "Wave: frame 1 exits to missing frame 5");
+    }
+}
@@ -0,0 +1,390 @@
+//! Minimal RIFF/WAV parsing and writing for character sounds.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::audio::AudioFormat;
+
+/// `WAVE_FORMAT_PCM`, the only encoding the sample-level helpers handle.
+const FORMAT_PCM: u16 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WavError {
+    /// The data isn't a well-formed WAV file.
+    Malformed(&'static str),
+    /// The file is valid but uses an encoding this operation doesn't handle.
+    Unsupported(&'static str),
+    /// Two files that must share a sample format don't.
+    FormatMismatch(AudioFormat, AudioFormat),
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(reason) => write!(f, "malformed WAV: {}", reason),
+            Self::Unsupported(reason) => write!(f, "unsupported WAV: {}", reason),
+            Self::FormatMismatch(a, b) => write!(
+                f,
+                "format mismatch: {}Hz/{}ch/{}bit vs {}Hz/{}ch/{}bit",
+                a.sample_rate,
+                a.channels,
+                a.bits_per_sample,
+                b.sample_rate,
+                b.channels,
+                b.bits_per_sample
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WavError {}
+
+/// Fields read from a WAV file's `fmt ` and `data` chunks.
+pub(crate) struct WavHeader {
+    pub format: AudioFormat,
+    pub format_tag: u16,
+    pub byte_rate: u32,
+    /// Byte range of the sample data, clamped to the buffer
+    pub data: Option<Range<usize>>,
+}
+
+pub(crate) fn parse_header(data: &[u8]) -> Result<WavHeader, WavError> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(WavError::Malformed("missing RIFF/WAVE header"));
+    }
+
+    let mut fmt = None;
+    let mut samples = None;
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let id = &data[pos..pos + 4];
+        let len = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().unwrap());
+        let body = pos + 8;
+
+        if id == b"fmt " {
+            if body + 16 > data.len() {
+                return Err(WavError::Malformed("truncated fmt chunk"));
+            }
+            let chunk = &data[body..body + 16];
+            let format = AudioFormat {
+                channels: u16::from_le_bytes([chunk[2], chunk[3]]),
+                sample_rate: u32::from_le_bytes(chunk[4..8].try_into().unwrap()),
+                bits_per_sample: u16::from_le_bytes([chunk[14], chunk[15]]),
+            };
+            let format_tag = u16::from_le_bytes([chunk[0], chunk[1]]);
+            let byte_rate = u32::from_le_bytes(chunk[8..12].try_into().unwrap());
+            fmt = Some((format, format_tag, byte_rate));
+        } else if id == b"data" {
+            // Some files overstate the data length, so clamp to what's actually there
+            samples = Some(body..body + (len as usize).min(data.len() - body));
+        }
+
+        let end = body
+            .checked_add(len as usize)
+            .ok_or(WavError::Malformed("oversized chunk"))?;
+        if end > data.len() && id != b"data" {
+            return Err(WavError::Malformed("truncated chunk"));
+        }
+        // Chunks are padded to an even length
+        pos = end
+            .checked_add(len as usize & 1)
+            .ok_or(WavError::Malformed("oversized chunk"))?;
+    }
+
+    let (format, format_tag, byte_rate) = fmt.ok_or(WavError::Malformed("missing fmt chunk"))?;
+    Ok(WavHeader {
+        format,
+        format_tag,
+        byte_rate,
+        data: samples,
+    })
+}
+
+/// Decode a 16-bit PCM WAV into its format and interleaved samples.
+///
+/// Chunks other than `fmt ` and `data`, such as `LIST` or `fact`, are skipped wherever
+/// they appear.
+pub fn extract_pcm16(data: &[u8]) -> Result<(AudioFormat, Vec<i16>), WavError> {
+    let header = parse_header(data)?;
+    if header.format_tag != FORMAT_PCM || header.format.bits_per_sample != 16 {
+        return Err(WavError::Unsupported("expected 16-bit PCM"));
+    }
+    let range = header
+        .data
+        .ok_or(WavError::Malformed("missing data chunk"))?;
+
+    let samples = data[range]
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    Ok((header.format, samples))
+}
+
+/// Encode interleaved 16-bit samples as a canonical 44-byte-header PCM WAV.
+pub fn write_pcm16(format: AudioFormat, samples: &[i16]) -> Vec<u8> {
+    let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    write_pcm(format, &bytes)
+}
+
+/// Wrap raw PCM bytes of any bit depth in a canonical 44-byte header.
+fn write_pcm(format: AudioFormat, pcm: &[u8]) -> Vec<u8> {
+    let block_align = format.channels as u32 * format.bits_per_sample.div_ceil(8) as u32;
+    let data_len = pcm.len() as u32;
+
+    let mut out = Vec::with_capacity(44 + pcm.len());
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVE");
+
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&FORMAT_PCM.to_le_bytes());
+    out.extend_from_slice(&format.channels.to_le_bytes());
+    out.extend_from_slice(&format.sample_rate.to_le_bytes());
+    out.extend_from_slice(&(format.sample_rate * block_align).to_le_bytes());
+    out.extend_from_slice(&(block_align as u16).to_le_bytes());
+    out.extend_from_slice(&format.bits_per_sample.to_le_bytes());
+
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    out.extend_from_slice(pcm);
+    out
+}
+
+/// Parse a PCM WAV of any bit depth into its format and sample data bytes.
+pub(crate) fn pcm_data(data: &[u8]) -> Result<(AudioFormat, &[u8]), WavError> {
+    let header = parse_header(data)?;
+    if header.format_tag != FORMAT_PCM {
+        return Err(WavError::Unsupported("expected PCM"));
+    }
+    let range = header
+        .data
+        .ok_or(WavError::Malformed("missing data chunk"))?;
+    Ok((header.format, &data[range]))
+}
+
+/// Join PCM WAV files end to end under a single header.
+///
+/// Every part must have the same sample format as the first; extra chunks such as
+/// `LIST` are dropped.
+pub fn concat(parts: &[&[u8]]) -> Result<Vec<u8>, WavError> {
+    let (&first, rest) = parts
+        .split_first()
+        .ok_or(WavError::Malformed("nothing to concatenate"))?;
+    let (format, first_pcm) = pcm_data(first)?;
+
+    let mut pcm = first_pcm.to_vec();
+    for part in rest {
+        let (part_format, part_pcm) = pcm_data(part)?;
+        if part_format != format {
+            return Err(WavError::FormatMismatch(format, part_format));
+        }
+        pcm.extend_from_slice(part_pcm);
+    }
+    Ok(write_pcm(format, &pcm))
+}
+
+/// Add silence before and after a PCM WAV, e.g. to line speech up with mouth frames.
+///
+/// Durations are rounded down to whole sample frames. 8-bit PCM is unsigned, so its
+/// silence is `0x80` rather than zero.
+pub fn pad_silence(wav: &[u8], lead_ms: u32, trail_ms: u32) -> Result<Vec<u8>, WavError> {
+    let (format, pcm) = pcm_data(wav)?;
+    let block_align = format.channels as usize * format.bits_per_sample.div_ceil(8) as usize;
+    if block_align == 0 {
+        return Err(WavError::Malformed("zero block align"));
+    }
+    let silence = if format.bits_per_sample == 8 { 0x80 } else { 0 };
+    let silence_len =
+        |ms: u32| (format.sample_rate as u64 * ms as u64 / 1000) as usize * block_align;
+
+    let lead = silence_len(lead_ms);
+    let trail = silence_len(trail_ms);
+    let mut padded = Vec::with_capacity(lead + pcm.len() + trail);
+    padded.resize(lead, silence);
+    padded.extend_from_slice(pcm);
+    padded.resize(padded.len() + trail, silence);
+    Ok(write_pcm(format, &padded))
+}
+
+/// Resample a 16-bit PCM WAV to another sample rate by linear interpolation.
+///
+/// Each channel is interpolated on its own. Files already at `target_hz` are
+/// returned unchanged.
+pub fn resample_pcm16(wav: &[u8], target_hz: u32) -> Result<Vec<u8>, WavError> {
+    let (format, samples) = extract_pcm16(wav)?;
+    if target_hz == 0 {
+        return Err(WavError::Unsupported("zero target sample rate"));
+    }
+    if format.sample_rate == 0 || format.channels == 0 {
+        return Err(WavError::Malformed("zero sample rate or channel count"));
+    }
+    if format.sample_rate == target_hz {
+        return Ok(wav.to_vec());
+    }
+
+    let out = resample(
+        &samples,
+        format.channels as usize,
+        format.sample_rate,
+        target_hz,
+    );
+    Ok(write_pcm16(
+        AudioFormat {
+            sample_rate: target_hz,
+            ..format
+        },
+        &out,
+    ))
+}
+
+/// Linearly interpolate interleaved samples from `from_hz` to `to_hz`, each channel on
+/// its own. Both rates and `channels` must be nonzero.
+pub(crate) fn resample(samples: &[i16], channels: usize, from_hz: u32, to_hz: u32) -> Vec<i16> {
+    let frames_in = samples.len() / channels;
+    let frames_out = (frames_in as u64 * to_hz as u64 / from_hz as u64) as usize;
+    let step = from_hz as f64 / to_hz as f64;
+
+    let mut out = Vec::with_capacity(frames_out * channels);
+    for frame in 0..frames_out {
+        let pos = frame as f64 * step;
+        let i = pos as usize;
+        let t = pos - i as f64;
+        let next = (i + 1).min(frames_in - 1);
+        for c in 0..channels {
+            let a = samples[i * channels + c] as f64;
+            let b = samples[next * channels + c] as f64;
+            out.push((a + (b - a) * t).round() as i16);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONO: AudioFormat = AudioFormat {
+        sample_rate: 11025,
+        channels: 1,
+        bits_per_sample: 16,
+    };
+
+    #[test]
+    fn test_extract_pcm16_skips_extra_chunks() {
+        let plain = write_pcm16(MONO, &[1, -2, 3]);
+        let (header, data) = plain.split_at(36);
+
+        // Odd-length LIST chunk (padded) and a fact chunk between fmt and data
+        let mut wav = header.to_vec();
+        wav.extend_from_slice(b"LIST\x05\0\0\0INFOx\0");
+        wav.extend_from_slice(b"fact\x04\0\0\0\x03\0\0\0");
+        wav.extend_from_slice(data);
+        let riff_len = wav.len() as u32 - 8;
+        wav[4..8].copy_from_slice(&riff_len.to_le_bytes());
+
+        assert_eq!(extract_pcm16(&wav).unwrap(), (MONO, vec![1, -2, 3]));
+        assert_eq!(
+            concat(&[&wav, &plain]).unwrap(),
+            write_pcm16(MONO, &[1, -2, 3, 1, -2, 3])
+        );
+    }
+
+    #[test]
+    fn test_parse_header_rejects_truncated_chunks() {
+        let plain = write_pcm16(MONO, &[1, -2, 3]);
+        let (header, data) = plain.split_at(36);
+
+        // A LIST chunk claiming to run far past the end of the file
+        let mut wav = header.to_vec();
+        wav.extend_from_slice(b"LIST\xFF\xFF\xFF\xFFINFO");
+        wav.extend_from_slice(data);
+        assert_eq!(
+            parse_header(&wav).err(),
+            Some(WavError::Malformed("truncated chunk"))
+        );
+
+        // An overstated data chunk is still clamped to what's there
+        let mut wav = plain.clone();
+        wav[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(extract_pcm16(&wav).unwrap(), (MONO, vec![1, -2, 3]));
+    }
+
+    #[test]
+    fn test_concat() {
+        let a = write_pcm16(MONO, &[1, 2, 3]);
+        let b = write_pcm16(MONO, &[-4, -5]);
+        let joined = concat(&[&a, &b]).unwrap();
+        assert_eq!(
+            extract_pcm16(&joined).unwrap(),
+            (MONO, vec![1, 2, 3, -4, -5])
+        );
+        assert_eq!(joined, write_pcm16(MONO, &[1, 2, 3, -4, -5]));
+
+        let other = AudioFormat {
+            sample_rate: 22050,
+            ..MONO
+        };
+        let c = write_pcm16(other, &[6]);
+        assert_eq!(
+            concat(&[&a, &c]),
+            Err(WavError::FormatMismatch(MONO, other))
+        );
+        assert!(concat(&[]).is_err());
+    }
+
+    #[test]
+    fn test_resample_pcm16() {
+        let stereo = AudioFormat {
+            channels: 2,
+            ..MONO
+        };
+        let wav = write_pcm16(stereo, &[0, 100, 10, 200, 20, 300]);
+
+        let up = resample_pcm16(&wav, 22050).unwrap();
+        let (format, samples) = extract_pcm16(&up).unwrap();
+        assert_eq!(format.sample_rate, 22050);
+        assert_eq!(samples.len(), 12);
+        // Channels interpolate independently; the last frame holds
+        assert_eq!(
+            samples,
+            [0, 100, 5, 150, 10, 200, 15, 250, 20, 300, 20, 300]
+        );
+
+        let down = resample_pcm16(&up, 11025).unwrap();
+        assert_eq!(
+            extract_pcm16(&down).unwrap(),
+            (stereo, vec![0, 100, 10, 200, 20, 300])
+        );
+
+        assert_eq!(resample_pcm16(&wav, 11025).unwrap(), wav);
+        assert!(resample_pcm16(&wav, 0).is_err());
+    }
+
+    #[test]
+    fn test_pad_silence() {
+        let stereo = AudioFormat {
+            sample_rate: 22050,
+            channels: 2,
+            bits_per_sample: 16,
+        };
+        let wav = write_pcm16(stereo, &[7, -7]);
+        let padded = pad_silence(&wav, 100, 20).unwrap();
+        // 2205 and 441 frames of 4 bytes each
+        assert_eq!(padded.len() - wav.len(), (2205 + 441) * 4);
+
+        let (format, samples) = extract_pcm16(&padded).unwrap();
+        assert_eq!(format, stereo);
+        assert_eq!(&samples[2205 * 2..2206 * 2], [7, -7]);
+        assert!(samples[..2205 * 2].iter().all(|&s| s == 0));
+
+        let eight_bit = AudioFormat {
+            bits_per_sample: 8,
+            ..MONO
+        };
+        let padded = pad_silence(&write_pcm(eight_bit, &[200]), 1, 0).unwrap();
+        let (_, pcm) = pcm_data(&padded).unwrap();
+        assert_eq!(pcm[..11], [0x80; 11]);
+        assert_eq!(pcm[11..], [200]);
+    }
+}
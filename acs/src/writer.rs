@@ -0,0 +1,542 @@
+//! Synthesize ACS files from scratch.
+//!
+//! `AcsBuilder` writes the smallest file the reader accepts: uncompressed images, no
+//! voice or tray icon, and a single localized entry. It's meant for tests and tools
+//! that need a character without shipping real (copyrighted) character files.
+//!
+//! `append` grows an existing file instead, for `Acs::import_animation` and
+//! `Acs::with_sound_replaced`.
+
+use crate::acs::{Animation, Frame};
+use crate::reader::{
+    ACS_SIGNATURE, AnimationEntry, AudioEntry, FLAG_BALLOON_DISABLED, FLAG_BALLOON_ENABLED,
+    FLAG_VOICE_OUTPUT, ImageEntry, VoiceInfo,
+};
+
+/// Language ID of the single localized entry (English).
+const LANG_ENGLISH: u16 = 9;
+
+/// Size of the file header: signature plus four locators.
+const HEADER_SIZE: usize = 4 + 4 * 8;
+
+pub(crate) struct BuilderImage {
+    pub width: u16,
+    pub height: u16,
+    /// Palette indices, top-down and unpadded
+    pub indices: Vec<u8>,
+}
+
+/// Builds a minimal ACS file; load the result with `Acs::new`.
+pub struct AcsBuilder {
+    name: String,
+    description: String,
+    guid: [u8; 16],
+    width: u16,
+    height: u16,
+    transparent_color: u8,
+    palette: Vec<[u8; 3]>,
+    images: Vec<BuilderImage>,
+    animations: Vec<Animation>,
+    sounds: Vec<Vec<u8>>,
+    balloon: bool,
+    voice: Option<VoiceInfo>,
+}
+
+impl AcsBuilder {
+    /// Start a character of the given frame size, with an empty palette.
+    pub fn new(width: u16, height: u16) -> AcsBuilder {
+        AcsBuilder {
+            name: String::new(),
+            description: String::new(),
+            guid: [0; 16],
+            width,
+            height,
+            transparent_color: 0,
+            palette: Vec::new(),
+            images: Vec::new(),
+            animations: Vec::new(),
+            sounds: Vec::new(),
+            balloon: true,
+            voice: None,
+        }
+    }
+
+    pub fn name(mut self, name: &str) -> AcsBuilder {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> AcsBuilder {
+        self.description = description.to_string();
+        self
+    }
+
+    pub fn guid(mut self, guid: [u8; 16]) -> AcsBuilder {
+        self.guid = guid;
+        self
+    }
+
+    /// Set the `[r, g, b]` palette (256 entries max).
+    pub fn palette(mut self, palette: Vec<[u8; 3]>) -> AcsBuilder {
+        self.palette = palette;
+        self
+    }
+
+    /// Set the palette index treated as transparent (default 0).
+    pub fn transparent_color(mut self, index: u8) -> AcsBuilder {
+        self.transparent_color = index;
+        self
+    }
+
+    /// Give the character TTS voice info, which also sets the voice output flag.
+    pub fn voice(mut self, voice: VoiceInfo) -> AcsBuilder {
+        self.voice = Some(voice);
+        self
+    }
+
+    /// Whether to store word balloon info (default true). Without it the character flags
+    /// mark the balloon as disabled.
+    pub fn balloon(mut self, balloon: bool) -> AcsBuilder {
+        self.balloon = balloon;
+        self
+    }
+
+    /// Add an image of palette indices, in top-down row order without padding.
+    ///
+    /// Images are numbered in the order they're added, starting at 0.
+    pub fn image(mut self, width: u16, height: u16, indices: Vec<u8>) -> AcsBuilder {
+        assert_eq!(
+            indices.len(),
+            width as usize * height as usize,
+            "image data must be width * height indices"
+        );
+        self.images.push(BuilderImage {
+            width,
+            height,
+            indices,
+        });
+        self
+    }
+
+    /// Add an animation. Frame durations are stored in 1/100 s, so they're rounded down
+    /// to a multiple of 10ms.
+    pub fn animation(mut self, animation: Animation) -> AcsBuilder {
+        self.animations.push(animation);
+        self
+    }
+
+    /// Add a sound from raw WAV bytes. Sounds are numbered in the order they're added.
+    pub fn sound(mut self, wav: Vec<u8>) -> AcsBuilder {
+        self.sounds.push(wav);
+        self
+    }
+
+    /// Serialize the character into ACS file bytes.
+    pub fn build(self) -> Vec<u8> {
+        let mut w = Writer {
+            buf: vec![0; HEADER_SIZE],
+        };
+
+        let localized = w.pos();
+        w.u16(1);
+        w.u16(LANG_ENGLISH);
+        w.string(&self.name);
+        w.string(&self.description);
+        w.string("");
+        let localized = (localized, w.pos() - localized);
+
+        let character_info = w.pos();
+        self.write_character_info(&mut w, localized);
+        let character_info = (character_info, w.pos() - character_info);
+
+        let animation_info = w.list(&self.animations, |w, animation| {
+            write_animation(w, animation)
+        });
+        let animation_list = w.pos();
+        w.u32(self.animations.len() as u32);
+        for (animation, locator) in self.animations.iter().zip(&animation_info) {
+            w.string(&animation.name);
+            w.locator(*locator);
+        }
+        let animation_list = (animation_list, w.pos() - animation_list);
+
+        let image_info = w.list(&self.images, write_image);
+        let image_list = w.pos();
+        w.u32(self.images.len() as u32);
+        for locator in &image_info {
+            w.locator(*locator);
+            w.u32(0); // checksum
+        }
+        let image_list = (image_list, w.pos() - image_list);
+
+        let sound_data = w.list(&self.sounds, |w, wav| w.buf.extend_from_slice(wav));
+        let audio_list = w.pos();
+        w.u32(self.sounds.len() as u32);
+        for locator in &sound_data {
+            w.locator(*locator);
+            w.u32(0); // checksum
+        }
+        let audio_list = (audio_list, w.pos() - audio_list);
+
+        let mut header = Writer { buf: Vec::new() };
+        header.u32(ACS_SIGNATURE);
+        header.locator(character_info);
+        header.locator(animation_list);
+        header.locator(image_list);
+        header.locator(audio_list);
+        w.buf[..HEADER_SIZE].copy_from_slice(&header.buf);
+
+        w.buf
+    }
+
+    fn write_character_info(&self, w: &mut Writer, localized: (u32, u32)) {
+        w.u16(0); // minor version
+        w.u16(2); // major version
+        w.locator(localized);
+        w.buf.extend_from_slice(&self.guid);
+        w.u16(self.width);
+        w.u16(self.height);
+        w.u8(self.transparent_color);
+        let balloon_flag = if self.balloon {
+            FLAG_BALLOON_ENABLED
+        } else {
+            FLAG_BALLOON_DISABLED
+        };
+        let voice_flag = if self.voice.is_some() {
+            FLAG_VOICE_OUTPUT
+        } else {
+            0
+        };
+        w.u32(balloon_flag | voice_flag);
+        w.u16(1); // animation set major version
+        w.u16(0); // animation set minor version
+
+        if let Some(voice) = &self.voice {
+            write_voice_info(w, voice);
+        }
+
+        if self.balloon {
+            // Balloon: 2 lines of 32 characters, black on tooltip yellow
+            w.u8(2);
+            w.u8(32);
+            for color in [[0, 0, 0], [0xFF, 0xFF, 0xE1], [0, 0, 0]] {
+                w.buf.extend_from_slice(&color);
+                w.u8(0);
+            }
+            w.string("MS Sans Serif");
+            w.i32(-13);
+            w.i32(400);
+            w.u8(0); // italic
+            w.u8(0); // charset
+        }
+
+        // Palette entries are RGBQUADs (B, G, R, reserved)
+        w.u32(self.palette.len() as u32);
+        for [r, g, b] in &self.palette {
+            w.buf.extend_from_slice(&[*b, *g, *r, 0]);
+        }
+
+        w.u8(0); // no tray icon
+        w.u16(0); // no states
+    }
+}
+
+/// What `append` adds to a file, or replaces in it.
+#[derive(Default)]
+pub(crate) struct Appended<'a> {
+    /// A new animation and the name to list it under
+    pub animation: Option<(&'a str, &'a Animation)>,
+    /// New images, numbered after the existing ones
+    pub images: &'a [BuilderImage],
+    /// New sounds, numbered after the existing ones
+    pub sounds: &'a [Vec<u8>],
+    /// WAV data to swap in for existing sounds, by index
+    pub replaced_sounds: &'a [(usize, &'a [u8])],
+}
+
+/// Append images, sounds and an animation to an existing ACS file, or replace its
+/// sounds.
+///
+/// The new images and sounds are numbered after the existing entries, which the
+/// animation must already refer to them by. Everything already in the file keeps its
+/// offset; the animation, image and audio lists are written again at the end with the
+/// new entries added and replaced sounds repointed, and the header is repointed at
+/// them. Replaced sounds' old bytes stay in the file, unreferenced.
+pub(crate) fn append(
+    data: &[u8],
+    lists: (&[AnimationEntry], &[ImageEntry], &[AudioEntry]),
+    appended: Appended,
+) -> Vec<u8> {
+    let (animation_entries, image_entries, audio_entries) = lists;
+    let mut w = Writer { buf: data.to_vec() };
+
+    let image_info = w.list(appended.images, write_image);
+    let sound_data = w.list(appended.sounds, |w, wav| w.buf.extend_from_slice(wav));
+    let replaced_data = w.list(appended.replaced_sounds, |w, (_, wav)| {
+        w.buf.extend_from_slice(wav)
+    });
+    let animation_info = appended.animation.map(|(name, animation)| {
+        let start = w.pos();
+        write_animation(&mut w, animation);
+        (name, (start, w.pos() - start))
+    });
+
+    let animation_list = w.pos();
+    w.u32((animation_entries.len() + animation_info.iter().len()) as u32);
+    for entry in animation_entries {
+        w.string(&entry.name);
+        w.locator((entry.locator.offset, entry.locator.size));
+    }
+    if let Some((name, locator)) = animation_info {
+        w.string(name);
+        w.locator(locator);
+    }
+    let animation_list = (animation_list, w.pos() - animation_list);
+
+    let image_list = w.pos();
+    w.u32((image_entries.len() + image_info.len()) as u32);
+    for entry in image_entries {
+        w.locator((entry.locator.offset, entry.locator.size));
+        w.u32(entry.checksum);
+    }
+    for locator in &image_info {
+        w.locator(*locator);
+        w.u32(0); // checksum
+    }
+    let image_list = (image_list, w.pos() - image_list);
+
+    let audio_list = w.pos();
+    w.u32((audio_entries.len() + sound_data.len()) as u32);
+    for (index, entry) in audio_entries.iter().enumerate() {
+        let replaced = appended
+            .replaced_sounds
+            .iter()
+            .zip(&replaced_data)
+            .rfind(|((replaced, _), _)| *replaced == index);
+        if let Some((_, locator)) = replaced {
+            w.locator(*locator);
+            w.u32(0); // checksum
+        } else {
+            w.locator((entry.locator.offset, entry.locator.size));
+            w.u32(entry.checksum);
+        }
+    }
+    for locator in &sound_data {
+        w.locator(*locator);
+        w.u32(0); // checksum
+    }
+    let audio_list = (audio_list, w.pos() - audio_list);
+
+    // The character info locator (after the signature) stays put
+    let mut header = Writer { buf: Vec::new() };
+    header.locator(animation_list);
+    header.locator(image_list);
+    header.locator(audio_list);
+    w.buf[12..HEADER_SIZE].copy_from_slice(&header.buf);
+
+    w.buf
+}
+
+fn write_voice_info(w: &mut Writer, voice: &VoiceInfo) {
+    w.buf.extend_from_slice(&voice.tts_engine_id);
+    w.buf.extend_from_slice(&voice.tts_mode_id);
+    w.u32(voice.speed);
+    w.u16(voice.pitch);
+    w.u8(voice.extra_data.is_some() as u8);
+    if let Some(extra) = &voice.extra_data {
+        w.u16(extra.lang_id);
+        w.string(&extra.lang_dialect);
+        w.u16(extra.gender);
+        w.u16(extra.age);
+        w.string(&extra.style);
+    }
+}
+
+fn write_animation(w: &mut Writer, animation: &Animation) {
+    w.string(&animation.name);
+    w.u8(animation.transition_type.into());
+    w.string(animation.return_animation.as_deref().unwrap_or(""));
+    w.u16(animation.frames.len() as u16);
+    for frame in &animation.frames {
+        write_frame(w, frame);
+    }
+}
+
+fn write_frame(w: &mut Writer, frame: &Frame) {
+    w.u16(frame.images.len() as u16);
+    for image in &frame.images {
+        w.u32(image.image_index as u32);
+        w.i16(image.x);
+        w.i16(image.y);
+    }
+
+    w.i16(frame.sound_index.map_or(-1, |i| i as i16));
+    w.u16((frame.duration_ms / 10) as u16);
+    w.i16(frame.exit_branch.map_or(-1, |i| i as i16));
+
+    w.u8(frame.branches.len() as u8);
+    for branch in &frame.branches {
+        w.u16(branch.frame_index as u16);
+        w.u16(branch.probability);
+    }
+
+    w.u8(frame.overlays.len() as u8);
+    for overlay in &frame.overlays {
+        w.u8(overlay.overlay_type.into());
+        w.u8(overlay.replace_enabled as u8);
+        w.u16(overlay.image_index as u16);
+        w.u8(0); // unknown
+        w.u8(0); // no region data
+        w.i16(overlay.x);
+        w.i16(overlay.y);
+        w.u16(overlay.width);
+        w.u16(overlay.height);
+    }
+}
+
+fn write_image(w: &mut Writer, image: &BuilderImage) {
+    w.u8(0); // unknown
+    w.u16(image.width);
+    w.u16(image.height);
+    w.u8(0); // uncompressed
+
+    // Rows are stored bottom-up, each padded to a multiple of 4 bytes
+    let width = image.width as usize;
+    let padding = ((width + 3) & !3) - width;
+    for row in image.indices.chunks_exact(width.max(1)).rev() {
+        w.buf.extend_from_slice(row);
+        w.buf.extend(std::iter::repeat_n(0, padding));
+    }
+
+    w.u32(0); // compressed region size
+    w.u32(0); // uncompressed region size
+}
+
+/// Little-endian byte sink mirroring the `AcsReader` primitives.
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn pos(&self) -> u32 {
+        self.buf.len() as u32
+    }
+
+    fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    fn u16(&mut self, v: u16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn i16(&mut self, v: i16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn i32(&mut self, v: i32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    /// Length-prefixed, null-terminated UTF-16LE string, as `AcsReader::read_string` expects.
+    fn string(&mut self, s: &str) {
+        let utf16: Vec<u16> = s.encode_utf16().collect();
+        self.u32(utf16.len() as u32);
+        if utf16.is_empty() {
+            return;
+        }
+        for unit in utf16 {
+            self.u16(unit);
+        }
+        self.u16(0);
+    }
+
+    fn locator(&mut self, (offset, size): (u32, u32)) {
+        self.u32(offset);
+        self.u32(size);
+    }
+
+    /// Write each item with `write`, returning the `(offset, size)` locator of each.
+    fn list<T>(&mut self, items: &[T], mut write: impl FnMut(&mut Writer, &T)) -> Vec<(u32, u32)> {
+        items
+            .iter()
+            .map(|item| {
+                let start = self.pos();
+                write(self, item);
+                (start, self.pos() - start)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acs::{Acs, FrameImage, TransitionType};
+
+    fn single_frame(name: &str, image_index: usize) -> Animation {
+        Animation {
+            name: name.to_string(),
+            frames: vec![Frame {
+                images: vec![FrameImage {
+                    image_index,
+                    x: 0,
+                    y: 0,
+                }],
+                duration_ms: 120,
+                sound_index: Some(0),
+                exit_branch: None,
+                branches: Vec::new(),
+                overlays: Vec::new(),
+            }],
+            return_animation: None,
+            transition_type: TransitionType::None,
+        }
+    }
+
+    #[test]
+    fn test_build_round_trip() {
+        let wav = crate::wav::write_pcm16(
+            crate::AudioFormat {
+                sample_rate: 8000,
+                channels: 1,
+                bits_per_sample: 16,
+            },
+            &[0; 80],
+        );
+        let data = AcsBuilder::new(2, 2)
+            .name("Tiny")
+            .palette(vec![[0, 255, 0], [255, 0, 0], [0, 0, 255]])
+            // Top row red, bottom row blue with a transparent pixel
+            .image(2, 2, vec![1, 1, 2, 0])
+            .animation(single_frame("RestPose", 0))
+            .sound(wav.clone())
+            .build();
+
+        let mut acs = Acs::new(data).unwrap();
+        assert_eq!(acs.character_info().name, "Tiny");
+        assert_eq!(
+            (acs.character_info().width, acs.character_info().height),
+            (2, 2)
+        );
+        assert_eq!(acs.animation_names(), ["RestPose"]);
+        assert_eq!(acs.sound(0).unwrap().data, wav);
+
+        let frame = &acs.animation("RestPose").unwrap().frames[0];
+        assert_eq!(frame.duration_ms, 120);
+        assert_eq!(frame.sound_index, Some(0));
+
+        let image = acs.image(0).unwrap();
+        assert_eq!(
+            image.data,
+            [
+                255, 0, 0, 255, 255, 0, 0, 255, //
+                0, 0, 255, 255, 0, 0, 0, 0,
+            ]
+        );
+    }
+}
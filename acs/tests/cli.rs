@@ -0,0 +1,73 @@
+#![cfg(feature = "png")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const BONZI: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../notes/files/Bonzi.acs");
+
+fn out_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_export_animation_frames() {
+    let dir = out_dir("export-greet");
+    let status = Command::new(env!("CARGO_BIN_EXE_acs"))
+        .args(["export", "--acs", BONZI, "--animation", "Greet"])
+        .arg("--out-dir")
+        .arg(&dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let frame_count = acs::Acs::from_path(BONZI)
+        .unwrap()
+        .animation("Greet")
+        .unwrap()
+        .frames
+        .len();
+    let pngs = fs::read_dir(&dir)
+        .unwrap()
+        .filter(|e| e.as_ref().unwrap().path().extension() == Some("png".as_ref()))
+        .count();
+    assert_eq!(pngs, frame_count);
+    assert!(dir.join("frame_000.png").exists());
+
+    let manifest = fs::read_to_string(dir.join("frames.json")).unwrap();
+    assert_eq!(manifest.matches("\"durationMs\"").count(), frame_count);
+}
+
+#[test]
+fn test_export_missing_animation_fails() {
+    let dir = out_dir("export-missing");
+    let status = Command::new(env!("CARGO_BIN_EXE_acs"))
+        .args(["export", "--acs", BONZI, "--animation", "NoSuchAnimation"])
+        .arg("--out-dir")
+        .arg(&dir)
+        .status()
+        .unwrap();
+    assert!(!status.success());
+}
+
+#[test]
+fn test_export_palette_png() {
+    let dir = out_dir("export-palette");
+    fs::create_dir_all(&dir).unwrap();
+    let out = dir.join("palette.png");
+    let status = Command::new(env!("CARGO_BIN_EXE_acs"))
+        .args(["export-palette", "--acs", BONZI, "--swatch", "8", "--out"])
+        .arg(&out)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let png = fs::read(&out).unwrap();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[12..16], b"IHDR");
+    let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+    assert_eq!((width, height), (128, 128));
+}
@@ -22,20 +22,28 @@ enum Commands {
     /// List available SAPI4 voices
     List,
 
-    /// Synthesize text to a WAV file or stdout
+    /// Synthesize text to a WAV file, stdout, or the speakers
     Speak {
         /// Text to speak
         #[arg(short, long)]
         text: String,
 
-        /// Output WAV file path (omit if using --stdout)
-        #[arg(short, long, required_unless_present = "stdout")]
+        /// Output WAV file path (omit if using --stdout or --play)
+        #[arg(short, long, required_unless_present_any = ["stdout", "play", "dry_run"])]
         output: Option<PathBuf>,
 
         /// Output WAV data to stdout (for piping to mpv, ffmpeg, etc.)
         #[arg(long, conflicts_with = "output")]
         stdout: bool,
 
+        /// Speak through the default audio device instead of writing WAV data (needs a sound card; --gain doesn't apply)
+        #[arg(long, conflicts_with_all = ["output", "stdout", "chunk_sentences", "resample"])]
+        play: bool,
+
+        /// Resample the output to this sample rate in Hz (e.g. 44100 for web playback)
+        #[arg(long)]
+        resample: Option<u32>,
+
         /// ACS file to read voice settings from (overrides other voice options)
         #[arg(long)]
         acs_file: Option<PathBuf>,
@@ -75,40 +83,61 @@ enum Commands {
         /// Audio gain/volume multiplier (default: 4.0 for louder output)
         #[arg(short, long, default_value = "4.0")]
         gain: f32,
+
+        /// Speak the text literally instead of interpreting control tags like \Spd=200\
+        #[arg(long)]
+        no_tags: bool,
+
+        /// Synthesize one sentence at a time, for long text that overflows SAPI4's buffers
+        #[arg(long)]
+        chunk_sentences: bool,
+
+        /// Fail if no voice matches instead of falling back to a similar or any installed voice
+        #[arg(long)]
+        strict_voice: bool,
+
+        /// Print the voice that would be used, with its speed and pitch, without synthesizing
+        #[arg(long)]
+        dry_run: bool,
     },
-}
 
-/// Amplify WAV audio data by a gain factor
-/// Assumes 16-bit PCM WAV format
-fn amplify_wav(wav_data: &mut [u8], gain: f32) {
-    // WAV header is typically 44 bytes, but let's find the data chunk properly
-    // Look for "data" marker
-    let data_pos = wav_data
-        .windows(4)
-        .position(|w| w == b"data")
-        .unwrap_or(36);
-
-    // Skip "data" marker (4 bytes) and size (4 bytes)
-    let audio_start = data_pos + 8;
-
-    if audio_start >= wav_data.len() {
-        return;
-    }
+    /// Replace one of an ACS file's sounds with speech in the character's voice
+    ReplaceSound {
+        /// ACS file to read the character and voice settings from
+        #[arg(long)]
+        acs: PathBuf,
 
-    // Process 16-bit samples (2 bytes each, little-endian)
-    let audio_data = &mut wav_data[audio_start..];
-    for chunk in audio_data.chunks_exact_mut(2) {
-        // Read 16-bit sample (little-endian)
-        let sample = i16::from_le_bytes([chunk[0], chunk[1]]);
+        /// Index of the sound to replace
+        #[arg(long)]
+        index: usize,
 
-        // Apply gain with saturation (clamp to i16 range)
-        let amplified = (sample as f32 * gain).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        /// Text to speak
+        #[arg(short, long)]
+        text: String,
 
-        // Write back
-        let bytes = amplified.to_le_bytes();
-        chunk[0] = bytes[0];
-        chunk[1] = bytes[1];
-    }
+        /// Output ACS file path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Audio gain/volume multiplier (default: 4.0 for louder output)
+        #[arg(short, long, default_value = "4.0")]
+        gain: f32,
+
+        /// Fail if no voice matches instead of falling back to a similar or any installed voice
+        #[arg(long)]
+        strict_voice: bool,
+    },
+}
+
+/// Amplify 16-bit PCM WAV audio by a gain factor, saturating at the sample range
+#[cfg(windows)]
+fn amplify_wav(wav_data: &[u8], gain: f32) -> Result<Vec<u8>, acs::wav::WavError> {
+    let (format, samples) = acs::wav::extract_pcm16(wav_data)?;
+    let amplified: Vec<i16> = samples
+        .iter()
+        .map(|&s| (s as f32 * gain).clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+        .collect();
+    Ok(acs::wav::write_pcm16(format, &amplified))
 }
 
 #[cfg(windows)]
@@ -151,7 +180,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let cli = Cli::parse();
 
-    let synth = sapi4::Synthesizer::new()?;
+    let mut synth = sapi4::Synthesizer::new()?;
 
     match cli.command {
         Commands::List => {
@@ -184,6 +213,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             text,
             output,
             stdout,
+            play,
             acs_file,
             voice,
             lang_id,
@@ -194,54 +224,37 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             speed,
             pitch,
             gain,
+            no_tags,
+            chunk_sentences,
+            strict_voice,
+            dry_run,
+            resample,
         } => {
-            // Determine voice criteria and speed/pitch from ACS file or CLI args
-            let (criteria, effective_speed, effective_pitch) = if let Some(ref acs_path) = acs_file {
-                // Read ACS file and extract voice info
-                let acs_data = std::fs::read(acs_path)
-                    .map_err(|e| format!("Failed to read ACS file: {}", e))?;
-                let acs = acs::Acs::new(acs_data)
-                    .map_err(|e| format!("Failed to parse ACS file: {}", e))?;
+            if no_tags {
+                synth.set_text_mode(sapi4::types::TextMode::Plain);
+            }
 
+            // Read the ACS file, if any, for its voice info
+            let acs = match acs_file {
+                Some(ref acs_path) => {
+                    let acs_data = std::fs::read(acs_path)
+                        .map_err(|e| format!("Failed to read ACS file: {}", e))?;
+                    Some(acs::Acs::new(acs_data).map_err(|e| format!("Failed to parse ACS file: {}", e))?)
+                }
+                None => None,
+            };
+
+            // Determine voice criteria and speed/pitch from ACS file or CLI args
+            let (criteria, effective_speed, effective_pitch) = if let Some(ref acs) = acs {
                 let char_info = acs.character_info();
                 eprintln!("Loading voice from ACS: {}", char_info.name);
-
-                if let Some(ref voice_info) = char_info.voice_info {
-                    let mut criteria = sapi4::VoiceCriteria::default();
-
-                    // Use extra_data if available for matching
-                    if let Some(ref extra) = voice_info.extra_data {
-                        criteria.language_id = Some(extra.lang_id);
-                        criteria.gender = Some(extra.gender);
-                        criteria.age = Some(extra.age);
-                        if !extra.lang_dialect.is_empty() {
-                            criteria.dialect = Some(extra.lang_dialect.clone());
-                        }
-                        if !extra.style.is_empty() {
-                            criteria.style = Some(extra.style.clone());
-                        }
-                    }
-
-                    // Use ACS speed/pitch, allowing CLI to override
-                    let acs_speed = Some(voice_info.speed);
-                    let acs_pitch = Some(voice_info.pitch);
-
-                    (
-                        criteria,
-                        speed.or(acs_speed),
-                        pitch.or(acs_pitch),
-                    )
-                } else {
+                if char_info.voice_info.is_none() {
                     eprintln!("Warning: ACS file has no voice info, using defaults");
-                    (
-                        sapi4::VoiceCriteria {
-                            name: Some("Adult Male #1".to_string()),
-                            ..Default::default()
-                        },
-                        speed,
-                        pitch,
-                    )
                 }
+
+                // Use ACS speed/pitch, allowing CLI to override
+                let (criteria, acs_speed, acs_pitch) = sapi4::criteria_from_acs(acs);
+                (criteria, speed.or(acs_speed), pitch.or(acs_pitch))
             } else {
                 // Build voice criteria from CLI arguments
                 let criteria = sapi4::VoiceCriteria {
@@ -262,7 +275,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     && criteria.style.is_none()
                 {
                     sapi4::VoiceCriteria {
-                        name: Some("Adult Male #1".to_string()),
+                        name: Some(sapi4::DEFAULT_VOICE.to_string()),
                         ..Default::default()
                     }
                 } else {
@@ -275,7 +288,45 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Format criteria description for status output
             let criteria_desc = format_criteria_desc(&criteria);
 
-            if stdout {
+            // Resolve the voice the same way the synthesis paths below do
+            let resolve_voice = || {
+                if strict_voice {
+                    synth.find_voice_by_criteria(&criteria)
+                } else {
+                    synth.find_voice_with_fallback(&criteria)
+                }
+            };
+
+            if dry_run {
+                let voice = resolve_voice()?;
+                eprintln!("Voice criteria: {}", criteria_desc);
+                eprintln!("Selected voice: \"{}\" ({})", voice.mode_name, voice.speaker);
+                eprintln!("Speed: {}", effective_speed.map_or("(default)".to_string(), |s| s.to_string()));
+                eprintln!("Pitch: {}", effective_pitch.map_or("(default)".to_string(), |p| p.to_string()));
+                return Ok(());
+            }
+
+            let synthesize = |path: &std::path::Path| {
+                if chunk_sentences {
+                    synth.synthesize_long_with_voice(&text, &resolve_voice()?, path, effective_speed, effective_pitch)
+                } else if strict_voice {
+                    synth.synthesize_to_file_with_criteria(&text, &criteria, path, effective_speed, effective_pitch)
+                } else if let Some(ref acs) = acs {
+                    synth.synthesize_acs_text(acs, &text, path, speed, pitch)
+                } else {
+                    synth.synthesize_with_fallback(&text, &criteria, path, effective_speed, effective_pitch)
+                }
+            };
+
+            if play {
+                eprintln!("Speaking...");
+                eprintln!("Voice criteria: {}", criteria_desc);
+                eprintln!("Text: \"{}\"", text);
+
+                synth.speak_live_with_voice(&text, &resolve_voice()?, effective_speed, effective_pitch)?;
+
+                eprintln!("Done!");
+            } else if stdout {
                 // Output to stdout - use temp file, then write to stdout
                 let temp_dir = std::env::temp_dir();
                 let temp_file = temp_dir.join(format!("sapi4_tts_{}.wav", std::process::id()));
@@ -285,7 +336,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 eprintln!("Voice criteria: {}", criteria_desc);
                 eprintln!("Text: \"{}\"", text);
 
-                synth.synthesize_to_file_with_criteria(&text, &criteria, &temp_file, effective_speed, effective_pitch)?;
+                synthesize(&temp_file)?;
 
                 // Read temp file and apply gain
                 let mut wav_data = std::fs::read(&temp_file)?;
@@ -293,7 +344,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                 // Apply gain amplification
                 if gain != 1.0 {
-                    amplify_wav(&mut wav_data, gain);
+                    wav_data = amplify_wav(&wav_data, gain)?;
+                }
+                if let Some(hz) = resample {
+                    wav_data = acs::wav::resample_pcm16(&wav_data, hz)?;
                 }
 
                 let mut stdout_handle = io::stdout().lock();
@@ -307,18 +361,63 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 eprintln!("Voice criteria: {}", criteria_desc);
                 eprintln!("Text: \"{}\"", text);
 
-                synth.synthesize_to_file_with_criteria(&text, &criteria, &output_path, effective_speed, effective_pitch)?;
+                synthesize(&output_path)?;
 
-                // Apply gain amplification to the output file
-                if gain != 1.0 {
+                // Apply gain amplification and resampling to the output file
+                if gain != 1.0 || resample.is_some() {
                     let mut wav_data = std::fs::read(&output_path)?;
-                    amplify_wav(&mut wav_data, gain);
+                    if gain != 1.0 {
+                        wav_data = amplify_wav(&wav_data, gain)?;
+                    }
+                    if let Some(hz) = resample {
+                        wav_data = acs::wav::resample_pcm16(&wav_data, hz)?;
+                    }
                     std::fs::write(&output_path, &wav_data)?;
                 }
 
                 eprintln!("Done! (gain: {}x)", gain);
             }
         }
+
+        Commands::ReplaceSound {
+            acs: acs_path,
+            index,
+            text,
+            output,
+            gain,
+            strict_voice,
+        } => {
+            let acs_data = std::fs::read(&acs_path)
+                .map_err(|e| format!("Failed to read ACS file: {}", e))?;
+            let acs = acs::Acs::new(acs_data)
+                .map_err(|e| format!("Failed to parse ACS file: {}", e))?;
+            if index >= acs.sound_count() {
+                return Err(format!("Sound index {} out of range ({} sounds)", index, acs.sound_count()).into());
+            }
+
+            let (criteria, speed, pitch) = sapi4::criteria_from_acs(&acs);
+            eprintln!("Synthesizing sound {} for {}", index, acs.character_info().name);
+            eprintln!("Voice criteria: {}", format_criteria_desc(&criteria));
+            let voice = if strict_voice {
+                synth.find_voice_by_criteria(&criteria)?
+            } else {
+                synth.find_voice_with_fallback(&criteria)?
+            };
+            eprintln!("Selected voice: \"{}\" ({})", voice.mode_name, voice.speaker);
+            eprintln!("Text: \"{}\"", text);
+
+            let temp_file = std::env::temp_dir().join(format!("sapi4_tts_{}.wav", std::process::id()));
+            synth.synthesize_to_file_with_voice(&text, &voice, &temp_file, speed, pitch)?;
+            let mut wav_data = std::fs::read(&temp_file)?;
+            let _ = std::fs::remove_file(&temp_file);
+            if gain != 1.0 {
+                wav_data = amplify_wav(&wav_data, gain)?;
+            }
+
+            let replaced = acs.with_sound_replaced(index, &wav_data)?;
+            std::fs::write(&output, &replaced)?;
+            eprintln!("Wrote {} ({} bytes)", output.display(), replaced.len());
+        }
     }
 
     Ok(())
@@ -0,0 +1,268 @@
+//! Voice selection criteria
+//!
+//! Pure mapping from ACS voice info to SAPI4 voice criteria and voice scoring,
+//! usable without Windows
+
+/// Voice used when nothing else selects one
+pub const DEFAULT_VOICE: &str = "Adult Male #1";
+
+/// Criteria for selecting a voice (all fields are optional filters)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VoiceCriteria {
+    pub name: Option<String>,
+    pub gender: Option<u16>,
+    pub age: Option<u16>,
+    pub language_id: Option<u16>,
+    pub dialect: Option<String>,
+    pub style: Option<String>,
+}
+
+/// Information about an available TTS voice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoiceInfo {
+    /// Engine mode GUID, as `GUID::to_u128`
+    pub mode_id: u128,
+    pub mode_name: String,
+    pub speaker: String,
+    pub gender: u16,
+    pub age: u16,
+    pub language_id: u16,
+    pub dialect: String,
+    pub style: String,
+}
+
+/// Score how well a voice matches the criteria, or `None` if any criterion fails
+///
+/// Text fields match case-insensitive substrings; numeric fields must be equal. An exact
+/// name match outscores a substring one, so "Adult Male #1" prefers itself to "#10".
+pub fn score_voice(criteria: &VoiceCriteria, voice: &VoiceInfo) -> Option<u32> {
+    let contains =
+        |haystack: &str, needle: &str| haystack.to_lowercase().contains(&needle.to_lowercase());
+    let mut score = 0u32;
+
+    if let Some(ref name) = criteria.name {
+        if !contains(&voice.mode_name, name) && !contains(&voice.speaker, name) {
+            return None;
+        }
+        score += 10;
+        if voice.mode_name.eq_ignore_ascii_case(name) || voice.speaker.eq_ignore_ascii_case(name) {
+            score += 5;
+        }
+    }
+    if let Some(gender) = criteria.gender {
+        if voice.gender != gender {
+            return None;
+        }
+        score += 20;
+    }
+    if let Some(age) = criteria.age {
+        if voice.age != age {
+            return None;
+        }
+        score += 15;
+    }
+    if let Some(lang_id) = criteria.language_id {
+        if voice.language_id != lang_id {
+            return None;
+        }
+        score += 25;
+    }
+    if let Some(ref dialect) = criteria.dialect {
+        if !contains(&voice.dialect, dialect) {
+            return None;
+        }
+        score += 15;
+    }
+    if let Some(ref style) = criteria.style {
+        if !contains(&voice.style, style) {
+            return None;
+        }
+        score += 10;
+    }
+
+    Some(score)
+}
+
+/// The best-scoring voice that matches all criteria; ties go to the first listed
+pub fn best_voice<'a>(voices: &'a [VoiceInfo], criteria: &VoiceCriteria) -> Option<&'a VoiceInfo> {
+    let mut best: Option<(&VoiceInfo, u32)> = None;
+    for voice in voices {
+        if let Some(score) = score_voice(criteria, voice) {
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((voice, score));
+            }
+        }
+    }
+    best.map(|(voice, _)| voice)
+}
+
+/// Fallback chain for when no voice matches: the criteria as given, then gender
+/// only, then any voice
+///
+/// Steps that would repeat the previous one are skipped.
+pub fn relaxed_criteria(criteria: &VoiceCriteria) -> Vec<VoiceCriteria> {
+    let mut chain = vec![criteria.clone()];
+    for step in [
+        VoiceCriteria {
+            gender: criteria.gender,
+            ..Default::default()
+        },
+        VoiceCriteria::default(),
+    ] {
+        if chain.last() != Some(&step) {
+            chain.push(step);
+        }
+    }
+    chain
+}
+
+/// Build voice criteria, speed and pitch from an ACS character's voice info
+///
+/// Characters without voice info get the default voice and no speed/pitch.
+/// CLI overrides are left to the caller.
+pub fn criteria_from_acs(acs: &acs::Acs) -> (VoiceCriteria, Option<u32>, Option<u16>) {
+    let Some(ref voice_info) = acs.character_info().voice_info else {
+        let criteria = VoiceCriteria {
+            name: Some(DEFAULT_VOICE.to_string()),
+            ..Default::default()
+        };
+        return (criteria, None, None);
+    };
+
+    let mut criteria = VoiceCriteria::default();
+
+    // Use extra_data if available for matching
+    if let Some(ref extra) = voice_info.extra_data {
+        criteria.language_id = Some(extra.lang_id);
+        criteria.gender = Some(extra.gender);
+        criteria.age = Some(extra.age);
+        if !extra.lang_dialect.is_empty() {
+            criteria.dialect = Some(extra.lang_dialect.clone());
+        }
+        if !extra.style.is_empty() {
+            criteria.style = Some(extra.style.clone());
+        }
+    }
+
+    (criteria, Some(voice_info.speed), Some(voice_info.pitch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use acs::writer::AcsBuilder;
+    use acs::{VoiceExtraData, VoiceInfo};
+
+    fn build(voice: Option<VoiceInfo>) -> acs::Acs {
+        let mut builder = AcsBuilder::new(1, 1).palette(vec![[0, 0, 0]]);
+        if let Some(voice) = voice {
+            builder = builder.voice(voice);
+        }
+        acs::Acs::new(builder.build()).unwrap()
+    }
+
+    #[test]
+    fn test_criteria_from_voice_extra_data() {
+        let acs = build(Some(VoiceInfo {
+            tts_engine_id: [1; 16],
+            tts_mode_id: [2; 16],
+            speed: 157,
+            pitch: 92,
+            extra_data_exists: true,
+            extra_data: Some(VoiceExtraData {
+                lang_id: 1033,
+                lang_dialect: "American".to_string(),
+                gender: 2,
+                age: 30,
+                style: String::new(),
+            }),
+        }));
+
+        let (criteria, speed, pitch) = criteria_from_acs(&acs);
+        assert_eq!(
+            criteria,
+            VoiceCriteria {
+                name: None,
+                gender: Some(2),
+                age: Some(30),
+                language_id: Some(1033),
+                dialect: Some("American".to_string()),
+                style: None,
+            }
+        );
+        assert_eq!((speed, pitch), (Some(157), Some(92)));
+    }
+
+    fn voice(mode_name: &str, gender: u16, language_id: u16) -> super::VoiceInfo {
+        super::VoiceInfo {
+            mode_id: 0,
+            mode_name: mode_name.to_string(),
+            speaker: String::new(),
+            gender,
+            age: 30,
+            language_id,
+            dialect: "American".to_string(),
+            style: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_relaxed_criteria_order() {
+        let voices = [
+            voice("Adult Female #1", 1, 1033),
+            voice("Adult Male #1", 2, 1033),
+            voice("Adult Male #2", 2, 1033),
+        ];
+        // A Japanese male voice isn't installed
+        let criteria = VoiceCriteria {
+            gender: Some(2),
+            language_id: Some(1041),
+            ..Default::default()
+        };
+
+        let chain = relaxed_criteria(&criteria);
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[1].gender, Some(2));
+        assert_eq!(chain[2], VoiceCriteria::default());
+
+        let matches: Vec<_> = chain
+            .iter()
+            .map(|c| best_voice(&voices, c).map(|v| v.mode_name.as_str()))
+            .collect();
+        assert_eq!(
+            matches,
+            [None, Some("Adult Male #1"), Some("Adult Female #1")]
+        );
+
+        // Without a gender the gender-only step would repeat "any voice"
+        assert_eq!(relaxed_criteria(&VoiceCriteria::default()).len(), 1);
+        let by_name = VoiceCriteria {
+            name: Some("male #2".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(relaxed_criteria(&by_name).len(), 2);
+        assert_eq!(score_voice(&by_name, &voices[2]), Some(10));
+        assert_eq!(score_voice(&by_name, &voices[1]), None);
+    }
+
+    #[test]
+    fn test_exact_name_wins() {
+        let voices = [
+            voice("Adult Male #10", 2, 1033),
+            voice("Adult Male #1", 2, 1033),
+        ];
+        let criteria = VoiceCriteria {
+            name: Some("adult male #1".to_string()),
+            ..Default::default()
+        };
+        let best = best_voice(&voices, &criteria).unwrap();
+        assert_eq!(best.mode_name, "Adult Male #1");
+    }
+
+    #[test]
+    fn test_criteria_without_voice_info() {
+        let (criteria, speed, pitch) = criteria_from_acs(&build(None));
+        assert_eq!(criteria.name.as_deref(), Some(DEFAULT_VOICE));
+        assert_eq!((speed, pitch), (None, None));
+    }
+}
@@ -17,6 +17,12 @@ pub const CLSID_AUDIODESTFILE: GUID = GUID::from_u128(
     0xd4623720_e4b9_11cf_8d56_00a0c9034a7e
 );
 
+// CLSID_MMAudioDest {CB96B400-C743-11cd-80E5-00AA003E4B50}
+#[cfg(windows)]
+pub const CLSID_MMAUDIODEST: GUID = GUID::from_u128(
+    0xcb96b400_c743_11cd_80e5_00aa003e4b50
+);
+
 // IID_ITTSEnumW {6B837B20-4A47-101B-931A-00AA0047BA4F}
 #[cfg(windows)]
 pub const IID_ITTSENUM: GUID = GUID::from_u128(
@@ -163,6 +163,14 @@ impl ITTSCentralA {
         (self.vtbl().UnRegister)(self.0.as_raw(), key)
     }
 
+    pub unsafe fn AudioPause(&self) -> HRESULT {
+        (self.vtbl().AudioPause)(self.0.as_raw())
+    }
+
+    pub unsafe fn AudioResume(&self) -> HRESULT {
+        (self.vtbl().AudioResume)(self.0.as_raw())
+    }
+
     pub unsafe fn AudioReset(&self) -> HRESULT {
         (self.vtbl().AudioReset)(self.0.as_raw())
     }
@@ -308,3 +316,50 @@ impl Clone for IAudioFile {
         Self(self.0.clone())
     }
 }
+
+/// IAudioMultiMediaDevice vtable
+#[repr(C)]
+pub struct IAudioMultiMediaDevice_Vtbl {
+    pub base__: windows::core::IUnknown_Vtbl,
+    pub CustomMessage: unsafe extern "system" fn(this: *mut c_void, msg: u32, data: SData) -> HRESULT,
+    pub DeviceNumGet: unsafe extern "system" fn(this: *mut c_void, device_id: *mut u32) -> HRESULT,
+    pub DeviceNumSet: unsafe extern "system" fn(this: *mut c_void, device_id: u32) -> HRESULT,
+}
+
+/// `WAVE_MAPPER`, the system's default wave output device
+pub const WAVE_MAPPER: u32 = 0xFFFF_FFFF;
+
+/// IAudioMultiMediaDevice interface wrapper, for the speaker output destination
+#[repr(transparent)]
+pub struct IAudioMultiMediaDevice(IUnknown);
+
+impl IAudioMultiMediaDevice {
+    pub const IID: GUID = GUID::from_u128(0xB68AD320_C743_11CD_80E5_00AA003E4B50);
+
+    #[inline]
+    fn vtbl(&self) -> &IAudioMultiMediaDevice_Vtbl {
+        unsafe { &*(*(self.0.as_raw() as *const *const IAudioMultiMediaDevice_Vtbl)) }
+    }
+
+    pub unsafe fn DeviceNumSet(&self, device_id: u32) -> HRESULT {
+        (self.vtbl().DeviceNumSet)(self.0.as_raw(), device_id)
+    }
+}
+
+unsafe impl windows::core::Interface for IAudioMultiMediaDevice {
+    type Vtable = IAudioMultiMediaDevice_Vtbl;
+    const IID: GUID = Self::IID;
+}
+
+impl std::ops::Deref for IAudioMultiMediaDevice {
+    type Target = IUnknown;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Clone for IAudioMultiMediaDevice {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
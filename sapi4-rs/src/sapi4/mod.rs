@@ -3,6 +3,12 @@
 //! This module provides Rust bindings to the legacy Microsoft Speech API 4.0,
 //! which includes the classic "Microsoft Sam" voice from Windows 2000.
 
+// Platform-independent, so their tests run on any host
+#[cfg(any(windows, test))]
+pub mod criteria;
+#[cfg(any(windows, test))]
+pub mod text;
+
 pub mod guids;
 pub mod types;
 
@@ -12,5 +18,8 @@ pub mod interfaces;
 #[cfg(windows)]
 mod synthesizer;
 
+#[cfg(any(windows, test))]
+#[cfg_attr(not(windows), allow(unused_imports))]
+pub use criteria::*;
 #[cfg(windows)]
 pub use synthesizer::*;
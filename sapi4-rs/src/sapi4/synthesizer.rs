@@ -5,6 +5,7 @@
 #![cfg(windows)]
 #![allow(non_snake_case)]
 
+use std::cell::RefCell;
 use std::ffi::c_void;
 use std::path::Path;
 use std::ptr;
@@ -17,8 +18,10 @@ use windows::Win32::UI::WindowsAndMessaging::{
     DispatchMessageW, PeekMessageW, TranslateMessage, MSG, PM_REMOVE,
 };
 
+use super::criteria::*;
 use super::guids::*;
 use super::interfaces::*;
+use super::text::split_sentences;
 use super::types::*;
 
 /// Error types for SAPI4 operations
@@ -42,37 +45,33 @@ pub enum Sapi4Error {
     Synthesize(String),
     #[error("Failed to get/set attributes: {0}")]
     Attributes(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid WAV output: {0}")]
+    InvalidWav(String),
+    #[error("No speech in progress")]
+    NotSpeaking,
 }
 
 pub type Result<T> = std::result::Result<T, Sapi4Error>;
 
-/// Information about an available TTS voice
-#[derive(Debug, Clone)]
-pub struct VoiceInfo {
-    pub mode_id: GUID,
-    pub mode_name: String,
-    pub speaker: String,
-    pub gender: u16,
-    pub age: u16,
-    pub language_id: u16,
-    pub dialect: String,
-    pub style: String,
-}
-
-/// Criteria for selecting a voice (all fields are optional filters)
-#[derive(Debug, Clone, Default)]
-pub struct VoiceCriteria {
-    pub name: Option<String>,
-    pub gender: Option<u16>,
-    pub age: Option<u16>,
-    pub language_id: Option<u16>,
-    pub dialect: Option<String>,
-    pub style: Option<String>,
+/// Speak text with an ACS character's voice, writing a WAV file to `out`
+///
+/// See `Synthesizer::synthesize_acs_text` to reuse a synthesizer or override the
+/// character's speed and pitch.
+pub fn speak_acs_text(acs: &acs::Acs, text: &str, out: &Path) -> Result<()> {
+    Synthesizer::new()?.synthesize_acs_text(acs, text, out, None, None)
 }
 
 /// SAPI4 TTS Synthesizer
+///
+/// COM is initialized single-threaded, so a synthesizer and any engine it hands out
+/// must stay on the thread that created it.
 pub struct Synthesizer {
     _com_initialized: bool,
+    text_mode: TextMode,
+    /// Engine of the speech in progress, for pause/resume
+    engine: RefCell<Option<ITTSCentralA>>,
 }
 
 impl Synthesizer {
@@ -86,9 +85,42 @@ impl Synthesizer {
         }
         Ok(Self {
             _com_initialized: true,
+            text_mode: TextMode::default(),
+            engine: RefCell::new(None),
         })
     }
 
+    /// Choose whether control tags in the text are interpreted (the default) or spoken literally
+    pub fn set_text_mode(&mut self, mode: TextMode) {
+        self.text_mode = mode;
+    }
+
+    /// The engine speaking right now, if any
+    ///
+    /// The returned reference keeps the engine (and its audio destination) alive after
+    /// the synthesizer lets go of it, so drop it before reusing the output file. Like
+    /// the synthesizer, it can only be used on the creating thread; code driven by the
+    /// message pump, such as a window procedure, is the place to call it from.
+    pub fn engine(&self) -> Option<ITTSCentralA> {
+        self.engine.borrow().clone()
+    }
+
+    /// Pause the speech in progress
+    pub fn pause(&self) -> Result<()> {
+        let engine = self.engine().ok_or(Sapi4Error::NotSpeaking)?;
+        unsafe { engine.AudioPause() }
+            .ok()
+            .map_err(|e| Sapi4Error::Synthesize(format!("AudioPause failed: {:?}", e)))
+    }
+
+    /// Resume speech paused with `pause`
+    pub fn resume(&self) -> Result<()> {
+        let engine = self.engine().ok_or(Sapi4Error::NotSpeaking)?;
+        unsafe { engine.AudioResume() }
+            .ok()
+            .map_err(|e| Sapi4Error::Synthesize(format!("AudioResume failed: {:?}", e)))
+    }
+
     /// List all available SAPI4 voices
     pub fn list_voices(&self) -> Result<Vec<VoiceInfo>> {
         unsafe {
@@ -108,7 +140,7 @@ impl Synthesizer {
                 }
 
                 voices.push(VoiceInfo {
-                    mode_id: mode_info.mode_id,
+                    mode_id: mode_info.mode_id.to_u128(),
                     mode_name: mode_info.mode_name_str(),
                     speaker: mode_info.speaker_str(),
                     gender: mode_info.gender,
@@ -132,90 +164,54 @@ impl Synthesizer {
     }
 
     /// Find a voice by multiple criteria (ACS-style matching)
-    /// Returns the first voice that matches ALL specified criteria
+    /// Returns the best-scoring voice that matches ALL specified criteria
     pub fn find_voice_by_criteria(&self, criteria: &VoiceCriteria) -> Result<VoiceInfo> {
-        let voices = self.list_voices()?;
-
-        // Score each voice based on how well it matches the criteria
-        let mut best_match: Option<(VoiceInfo, u32)> = None;
-
-        for voice in voices {
-            let mut score = 0u32;
-            let mut matched = true;
-
-            // Name matching (partial, case-insensitive)
-            if let Some(ref name) = criteria.name {
-                let name_lower = name.to_lowercase();
-                if voice.mode_name.to_lowercase().contains(&name_lower)
-                    || voice.speaker.to_lowercase().contains(&name_lower)
-                {
-                    score += 10;
-                } else {
-                    matched = false;
-                }
-            }
-
-            // Gender matching (exact)
-            if let Some(gender) = criteria.gender {
-                if voice.gender == gender {
-                    score += 20;
-                } else {
-                    matched = false;
-                }
-            }
-
-            // Age matching (exact)
-            if let Some(age) = criteria.age {
-                if voice.age == age {
-                    score += 15;
-                } else {
-                    matched = false;
-                }
-            }
-
-            // Language ID matching (exact)
-            if let Some(lang_id) = criteria.language_id {
-                if voice.language_id == lang_id {
-                    score += 25;
-                } else {
-                    matched = false;
-                }
-            }
-
-            // Dialect matching (partial, case-insensitive)
-            if let Some(ref dialect) = criteria.dialect {
-                let dialect_lower = dialect.to_lowercase();
-                if voice.dialect.to_lowercase().contains(&dialect_lower) {
-                    score += 15;
-                } else {
-                    matched = false;
-                }
-            }
+        best_voice(&self.list_voices()?, criteria)
+            .cloned()
+            .ok_or_else(|| Sapi4Error::VoiceNotFound(format!("{:?}", criteria)))
+    }
 
-            // Style matching (partial, case-insensitive)
-            if let Some(ref style) = criteria.style {
-                let style_lower = style.to_lowercase();
-                if voice.style.to_lowercase().contains(&style_lower) {
-                    score += 10;
-                } else {
-                    matched = false;
-                }
+    /// Find a voice, relaxing the criteria step by step (see `relaxed_criteria`) when
+    /// nothing matches, and logging each relaxation to stderr
+    pub fn find_voice_with_fallback(&self, criteria: &VoiceCriteria) -> Result<VoiceInfo> {
+        let voices = self.list_voices()?;
+        for (i, step) in relaxed_criteria(criteria).iter().enumerate() {
+            if i > 0 {
+                eprintln!("No voice matches {:?}, trying {:?}", criteria, step);
             }
-
-            if matched {
-                if let Some((_, best_score)) = &best_match {
-                    if score > *best_score {
-                        best_match = Some((voice, score));
-                    }
-                } else {
-                    best_match = Some((voice, score));
-                }
+            if let Some(voice) = best_voice(&voices, step) {
+                return Ok(voice.clone());
             }
         }
+        Err(Sapi4Error::VoiceNotFound(format!("{:?} (no voices installed)", criteria)))
+    }
 
-        best_match
-            .map(|(voice, _)| voice)
-            .ok_or_else(|| Sapi4Error::VoiceNotFound(format!("{:?}", criteria)))
+    /// Synthesize text to a WAV file, falling back to a similar or any installed voice
+    /// when none matches the criteria
+    pub fn synthesize_with_fallback(
+        &self,
+        text: &str,
+        criteria: &VoiceCriteria,
+        output_path: &Path,
+        speed: Option<u32>,
+        pitch: Option<u16>,
+    ) -> Result<()> {
+        let voice = self.find_voice_with_fallback(criteria)?;
+        self.synthesize_to_file_with_voice(text, &voice, output_path, speed, pitch)
+    }
+
+    /// Synthesize text to a WAV file with an ACS character's voice, falling back to a
+    /// similar voice if it isn't installed. `speed` and `pitch` override the character's.
+    pub fn synthesize_acs_text(
+        &self,
+        acs: &acs::Acs,
+        text: &str,
+        output_path: &Path,
+        speed: Option<u32>,
+        pitch: Option<u16>,
+    ) -> Result<()> {
+        let (criteria, acs_speed, acs_pitch) = criteria_from_acs(acs);
+        self.synthesize_with_fallback(text, &criteria, output_path, speed.or(acs_speed), pitch.or(acs_pitch))
     }
 
     /// Synthesize text to a WAV file using voice name
@@ -239,6 +235,53 @@ impl Synthesizer {
         )
     }
 
+    /// Synthesize long text one sentence at a time into a single WAV file
+    ///
+    /// Keeps each `TextData` call small enough for SAPI4's buffers and the fixed-length
+    /// message pump, then joins the sentences' PCM under one header.
+    pub fn synthesize_long(
+        &self,
+        text: &str,
+        criteria: &VoiceCriteria,
+        output_path: &Path,
+        speed: Option<u32>,
+        pitch: Option<u16>,
+    ) -> Result<()> {
+        let voice = self.find_voice_by_criteria(criteria)?;
+        self.synthesize_long_with_voice(text, &voice, output_path, speed, pitch)
+    }
+
+    /// Synthesize long text one sentence at a time with an already resolved voice
+    pub fn synthesize_long_with_voice(
+        &self,
+        text: &str,
+        voice: &VoiceInfo,
+        output_path: &Path,
+        speed: Option<u32>,
+        pitch: Option<u16>,
+    ) -> Result<()> {
+        let sentences = split_sentences(text);
+        if sentences.len() <= 1 {
+            return self.synthesize_to_file_with_voice(text, voice, output_path, speed, pitch);
+        }
+
+        let mut parts = Vec::with_capacity(sentences.len());
+        for (i, sentence) in sentences.iter().enumerate() {
+            let part_path = std::env::temp_dir()
+                .join(format!("sapi4_tts_{}_part{}.wav", std::process::id(), i));
+            let result = self
+                .synthesize_to_file_with_voice(sentence, voice, &part_path, speed, pitch)
+                .and_then(|()| Ok(std::fs::read(&part_path)?));
+            let _ = std::fs::remove_file(&part_path);
+            parts.push(result?);
+        }
+
+        let parts: Vec<&[u8]> = parts.iter().map(Vec::as_slice).collect();
+        let wav = acs::wav::concat(&parts).map_err(|e| Sapi4Error::InvalidWav(e.to_string()))?;
+        std::fs::write(output_path, wav)?;
+        Ok(())
+    }
+
     /// Synthesize text to a WAV file using voice criteria
     pub fn synthesize_to_file_with_criteria(
         &self,
@@ -248,15 +291,23 @@ impl Synthesizer {
         speed: Option<u32>,
         pitch: Option<u16>,
     ) -> Result<()> {
-        unsafe {
-            // Find the voice
-            let voice = self.find_voice_by_criteria(criteria)?;
-
-            // Create TTS enumerator
-            let enumerator: ITTSEnumA =
-                CoCreateInstance(&CLSID_TTSENUMERATOR, None, CLSCTX_ALL)
-                    .map_err(|e| Sapi4Error::EnumeratorCreate(format!("{:?}", e)))?;
+        let voice = self.find_voice_by_criteria(criteria)?;
+        self.synthesize_to_file_with_voice(text, &voice, output_path, speed, pitch)
+    }
 
+    /// Synthesize text to a WAV file with an already resolved voice
+    ///
+    /// The engine is selected by the voice's mode GUID, so this is exactly the voice
+    /// passed in even when other voices' names contain its name.
+    pub fn synthesize_to_file_with_voice(
+        &self,
+        text: &str,
+        voice: &VoiceInfo,
+        output_path: &Path,
+        speed: Option<u32>,
+        pitch: Option<u16>,
+    ) -> Result<()> {
+        unsafe {
             // Create audio destination file
             let audio_dest: IAudioFile =
                 CoCreateInstance(&CLSID_AUDIODESTFILE, None, CLSCTX_ALL)
@@ -272,99 +323,193 @@ impl Synthesizer {
                 return Err(Sapi4Error::SetOutputFile(format!("{:?}", hr)));
             }
 
-            // Select the voice
-            let mut central_ptr: *mut c_void = ptr::null_mut();
-            let audio_dest_unknown: IUnknown = audio_dest.cast().unwrap();
+            let central = self.select_engine(voice, &audio_dest.cast().unwrap(), speed, pitch)?;
+            self.speak(&central, text)?;
 
-            let hr = enumerator.Select(
-                voice.mode_id,
-                &mut central_ptr,
-                audio_dest_unknown.as_raw(),
-            );
-            if hr.is_err() {
-                return Err(Sapi4Error::SelectVoice(format!("{:?}", hr)));
-            }
+            // Let go of the engine so the output file is closed once we return
+            self.engine.borrow_mut().take();
 
-            if central_ptr.is_null() {
-                return Err(Sapi4Error::SelectVoice("Got null ITTSCentral".to_string()));
-            }
+            // Flush audio file to ensure all data is written
+            let _ = audio_dest.Flush();
 
-            // Cast to ITTSCentralA
-            // NOTE: This is a raw pointer, we need to be careful about ownership
-            let central_unknown = IUnknown::from_raw(central_ptr);
-            let central: ITTSCentralA = central_unknown.cast()
-                .map_err(|e| Sapi4Error::SelectVoice(format!("Cast to ITTSCentralA failed: {:?}", e)))?;
-
-            // Set speed and pitch if specified
-            if speed.is_some() || pitch.is_some() {
-                if let Ok(attrs) = central.cast::<ITTSAttributesA>() {
-                    if let Some(s) = speed {
-                        let _ = attrs.SpeedSet(s);
-                    }
-                    if let Some(p) = pitch {
-                        let _ = attrs.PitchSet(p);
-                    }
-                }
-            }
+            // Process any remaining messages after flush
+            pump_messages();
 
-            // Prepare text data (null-terminated for ANSI)
-            let mut text_with_null = text.as_bytes().to_vec();
-            text_with_null.push(0);
-            let text_data = SData::from_bytes(&text_with_null);
-
-            // Reset audio before starting
-            let _ = central.AudioReset();
-
-            // Synthesize (without notification sink for simplicity)
-            // Use TTSDATAFLAG_TAGGED (1) like the reference implementation
-            let hr = central.TextData(
-                VoiceCharset::Text,
-                TTSDATAFLAG_TAGGED,
-                text_data,
-                ptr::null_mut(), // no notification sink
-                GUID::zeroed(),
-            );
+            // Additional short wait after flush
+            std::thread::sleep(std::time::Duration::from_millis(500));
+
+            Ok(())
+        }
+    }
+
+    /// Speak text through the default audio device
+    ///
+    /// Needs a working wave output device; without one, selecting the voice fails.
+    /// Returns once the estimated speaking time has passed, keeping the engine until
+    /// the next call so `pause`/`resume` still reach any speech that's left.
+    pub fn speak_live(
+        &self,
+        text: &str,
+        criteria: &VoiceCriteria,
+        speed: Option<u32>,
+        pitch: Option<u16>,
+    ) -> Result<()> {
+        let voice = self.find_voice_by_criteria(criteria)?;
+        self.speak_live_with_voice(text, &voice, speed, pitch)
+    }
+
+    /// Speak text through the default audio device with an already resolved voice
+    pub fn speak_live_with_voice(
+        &self,
+        text: &str,
+        voice: &VoiceInfo,
+        speed: Option<u32>,
+        pitch: Option<u16>,
+    ) -> Result<()> {
+        unsafe {
+            let audio_dest: IAudioMultiMediaDevice =
+                CoCreateInstance(&CLSID_MMAUDIODEST, None, CLSCTX_ALL)
+                    .map_err(|e| Sapi4Error::AudioDestCreate(format!("{:?}", e)))?;
+            let hr = audio_dest.DeviceNumSet(WAVE_MAPPER);
             if hr.is_err() {
-                return Err(Sapi4Error::Synthesize(format!("TextData failed: {:?}", hr)));
+                return Err(Sapi4Error::AudioDestCreate(format!("DeviceNumSet failed: {:?}", hr)));
             }
 
-            // Run a Windows message pump to allow COM to process
-            // SAPI4 synthesis is asynchronous and requires message processing
-            let wait_ms = 2000 + (text.len() as u64 * 100);
-            let start = std::time::Instant::now();
-            let mut msg = MSG::default();
-
-            while start.elapsed().as_millis() < wait_ms as u128 {
-                // Process any pending Windows messages
-                while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
-                    let _ = TranslateMessage(&msg);
-                    DispatchMessageW(&msg);
+            let central = self.select_engine(voice, &audio_dest.cast().unwrap(), speed, pitch)?;
+            self.speak(&central, text)
+        }
+    }
+
+    /// Select the engine for a voice by its mode GUID, sending its audio to `audio_dest`
+    unsafe fn select_engine(
+        &self,
+        voice: &VoiceInfo,
+        audio_dest: &IUnknown,
+        speed: Option<u32>,
+        pitch: Option<u16>,
+    ) -> Result<ITTSCentralA> {
+        // Create TTS enumerator
+        let enumerator: ITTSEnumA =
+            CoCreateInstance(&CLSID_TTSENUMERATOR, None, CLSCTX_ALL)
+                .map_err(|e| Sapi4Error::EnumeratorCreate(format!("{:?}", e)))?;
+
+        // Select the voice
+        let mut central_ptr: *mut c_void = ptr::null_mut();
+        let hr = enumerator.Select(GUID::from_u128(voice.mode_id), &mut central_ptr, audio_dest.as_raw());
+        if hr.is_err() {
+            return Err(Sapi4Error::SelectVoice(format!("{:?}", hr)));
+        }
+
+        if central_ptr.is_null() {
+            return Err(Sapi4Error::SelectVoice("Got null ITTSCentral".to_string()));
+        }
+
+        // Cast to ITTSCentralA
+        // Like any COM out-parameter, the pointer from Select is already AddRef'd and
+        // the reference is ours, so from_raw takes it over without another AddRef. The
+        // cast adds the ITTSCentralA reference and central_unknown releases Select's
+        // when it drops at the end of this function, leaving exactly one.
+        let central_unknown = IUnknown::from_raw(central_ptr);
+        let central: ITTSCentralA = central_unknown.cast()
+            .map_err(|e| Sapi4Error::SelectVoice(format!("Cast to ITTSCentralA failed: {:?}", e)))?;
+
+        // Set speed and pitch if specified
+        if speed.is_some() || pitch.is_some() {
+            if let Ok(attrs) = central.cast::<ITTSAttributesA>() {
+                if let Some(s) = speed {
+                    let _ = attrs.SpeedSet(s);
+                }
+                if let Some(p) = pitch {
+                    let _ = attrs.PitchSet(p);
                 }
-                // Small sleep to avoid busy-waiting
-                std::thread::sleep(std::time::Duration::from_millis(10));
             }
+        }
 
-            // Flush audio file to ensure all data is written
-            let _ = audio_dest.Flush();
+        Ok(central)
+    }
 
-            // Process any remaining messages after flush
-            while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
-                let _ = TranslateMessage(&msg);
-                DispatchMessageW(&msg);
-            }
+    /// Queue text on an engine and pump messages until it should have finished
+    unsafe fn speak(&self, central: &ITTSCentralA, text: &str) -> Result<()> {
+        // Prepare text data (null-terminated for ANSI)
+        let mut text_with_null = text.as_bytes().to_vec();
+        text_with_null.push(0);
+        let text_data = SData::from_bytes(&text_with_null);
+
+        // Reset audio before starting
+        let _ = central.AudioReset();
+
+        // Synthesize (without notification sink for simplicity)
+        // Tagged by default (TTSDATAFLAG_TAGGED) like the reference implementation
+        let hr = central.TextData(
+            VoiceCharset::Text,
+            self.text_mode.text_data_flags(),
+            text_data,
+            ptr::null_mut(), // no notification sink
+            GUID::zeroed(),
+        );
+        if hr.is_err() {
+            return Err(Sapi4Error::Synthesize(format!("TextData failed: {:?}", hr)));
+        }
+        *self.engine.borrow_mut() = Some(central.clone());
 
-            // Additional short wait after flush
-            std::thread::sleep(std::time::Duration::from_millis(500));
+        // Run a Windows message pump to allow COM to process
+        // SAPI4 synthesis is asynchronous and requires message processing
+        let wait_ms = 2000 + (text.len() as u64 * 100);
+        let start = std::time::Instant::now();
 
-            Ok(())
+        while start.elapsed().as_millis() < wait_ms as u128 {
+            pump_messages();
+            // Small sleep to avoid busy-waiting
+            std::thread::sleep(std::time::Duration::from_millis(10));
         }
+
+        Ok(())
+    }
+}
+
+/// Process any pending Windows messages
+unsafe fn pump_messages() {
+    let mut msg = MSG::default();
+    while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
+        let _ = TranslateMessage(&msg);
+        DispatchMessageW(&msg);
     }
 }
 
 impl Drop for Synthesizer {
     fn drop(&mut self) {
+        // Release the engine before COM goes away
+        self.engine.get_mut().take();
         unsafe {
             CoUninitialize();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_without_speech_fails() {
+        let synth = Synthesizer::new().unwrap();
+        assert!(synth.engine().is_none());
+        assert!(matches!(synth.pause(), Err(Sapi4Error::NotSpeaking)));
+        assert!(matches!(synth.resume(), Err(Sapi4Error::NotSpeaking)));
+    }
+
+    #[test]
+    #[ignore = "needs a SAPI4 voice installed"]
+    fn synthesize_twice() {
+        // An over-release of the engine would crash on the second round
+        let synth = Synthesizer::new().unwrap();
+        let criteria = VoiceCriteria::default();
+        for i in 0..2 {
+            let path = std::env::temp_dir().join(format!("sapi4_test_{}_{}.wav", std::process::id(), i));
+            synth.synthesize_to_file_with_criteria("Hello.", &criteria, &path, None, None).unwrap();
+            let wav = std::fs::read(&path).unwrap();
+            let _ = std::fs::remove_file(&path);
+            assert_eq!(&wav[..4], b"RIFF");
+        }
+    }
+}
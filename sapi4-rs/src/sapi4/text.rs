@@ -0,0 +1,57 @@
+//! Text preparation for synthesis
+
+/// Split text into sentences for synthesizing one at a time
+///
+/// A sentence ends at a run of `.`, `!` or `?` followed by whitespace or the end of
+/// the text; the punctuation stays with its sentence. Surrounding whitespace is
+/// trimmed and empty pieces are dropped, so text without terminators is one sentence.
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if !matches!(next, '.' | '!' | '?') {
+                break;
+            }
+            end = j + next.len_utf8();
+            chars.next();
+        }
+        if chars.peek().is_none_or(|&(_, next)| next.is_whitespace()) {
+            sentences.push(&text[start..end]);
+            start = end;
+        }
+    }
+    sentences.push(&text[start..]);
+
+    sentences
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_sentences() {
+        assert_eq!(
+            split_sentences("Hello there. How are you?! I'm fine...  Bye"),
+            ["Hello there.", "How are you?!", "I'm fine...", "Bye"]
+        );
+        // No break inside numbers, versions or abbreviations without a following space
+        assert_eq!(
+            split_sentences("Version 3.0.7 costs $1.50.\nOK!"),
+            ["Version 3.0.7 costs $1.50.", "OK!"]
+        );
+        assert_eq!(split_sentences("no terminator"), ["no terminator"]);
+        assert!(split_sentences("  ").is_empty());
+    }
+}
@@ -6,12 +6,17 @@
 use windows::core::GUID;
 
 // Constants from speech.h
+#[cfg(windows)]
 pub const SVFN_LEN: usize = 262;
+#[cfg(windows)]
 pub const LANG_LEN: usize = 64;
+#[cfg(windows)]
 pub const TTSI_NAMELEN: usize = SVFN_LEN;
+#[cfg(windows)]
 pub const TTSI_STYLELEN: usize = SVFN_LEN;
 
 /// Voice character set for TextData
+#[cfg(windows)]
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VoiceCharset {
@@ -21,11 +26,15 @@ pub enum VoiceCharset {
 }
 
 /// Gender constants
+#[cfg(windows)]
 pub const GENDER_NEUTRAL: u16 = 0;
+#[cfg(windows)]
 pub const GENDER_FEMALE: u16 = 1;
+#[cfg(windows)]
 pub const GENDER_MALE: u16 = 2;
 
 /// SDATA structure - pointer to data with size
+#[cfg(windows)]
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct SData {
@@ -33,6 +42,7 @@ pub struct SData {
     pub size: u32,
 }
 
+#[cfg(windows)]
 impl SData {
     pub fn from_str(s: &str) -> Self {
         Self {
@@ -50,6 +60,7 @@ impl SData {
 }
 
 /// LANGUAGE structure (ANSI version)
+#[cfg(windows)]
 #[repr(C)]
 #[derive(Debug, Clone)]
 pub struct LanguageA {
@@ -57,6 +68,7 @@ pub struct LanguageA {
     pub dialect: [u8; LANG_LEN],
 }
 
+#[cfg(windows)]
 impl Default for LanguageA {
     fn default() -> Self {
         Self {
@@ -136,6 +148,7 @@ impl TtsModeInfoA {
 }
 
 /// TTSMOUTH structure - lip sync data
+#[cfg(windows)]
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct TtsMouth {
@@ -150,4 +163,41 @@ pub struct TtsMouth {
 }
 
 // Text data flags
+#[cfg(any(windows, test))]
 pub const TTSDATAFLAG_TAGGED: u32 = 1;
+
+/// How the engine interprets text passed to `TextData`
+///
+/// Tagged text may embed control tags between backslashes, e.g. `\Spd=200\` (speed),
+/// `\Pit=120\` (pitch), `\Vol=50000\` (volume), `\Pau=500\` (pause in ms) and
+/// `\Prn=...\` (pronunciation). Plain text is spoken literally.
+#[cfg(any(windows, test))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextMode {
+    #[default]
+    Tagged,
+    Plain,
+}
+
+#[cfg(any(windows, test))]
+impl TextMode {
+    /// The `flags` argument to pass to `TextData` (always with `VoiceCharset::Text`)
+    pub fn text_data_flags(self) -> u32 {
+        match self {
+            TextMode::Tagged => TTSDATAFLAG_TAGGED,
+            TextMode::Plain => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_mode_flags() {
+        assert_eq!(TextMode::default(), TextMode::Tagged);
+        assert_eq!(TextMode::Tagged.text_data_flags(), TTSDATAFLAG_TAGGED);
+        assert_eq!(TextMode::Plain.text_data_flags() & TTSDATAFLAG_TAGGED, 0);
+    }
+}